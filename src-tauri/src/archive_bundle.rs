@@ -0,0 +1,95 @@
+// 移行結果一式（結果CSV・JUnitレポート・サマリーJSON）を1つのzipアーカイブへまとめるモジュール
+// ステークホルダーへの共有や実行結果のアーカイブを、result_csv_path等バラバラのファイルではなく
+// 単一のダウンロード可能な成果物として行えるようにする。async_zipの`write_entry_whole`で1ファイルずつ書き込む。
+// 各ファイルは全体をメモリへ読み込んでから書き込むため、ピークメモリは最大のファイル1つ分になる
+// （エントリ単位のストリーミング書き込みは行わない）
+use anyhow::{Context, Result};
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+
+/// zip内の固定トップレベルフォルダ名。毎回同じ名前にすることで、再実行した結果同士を
+/// 中身のdiffだけで比較できるようにする（決定的なレイアウト）
+const BUNDLE_ROOT_FOLDER: &str = "migration-result";
+
+/// バンドルへ含める成果物一式
+pub struct BundleInput<'a> {
+  pub result_csv_path: &'a Path,
+  /// Chatter移行など、JUnitレポートを生成しない処理からは`None`を渡す
+  pub junit_report_path: Option<&'a Path>,
+  pub summaries_json: &'a str,
+  /// zip内ファイル名と実ファイルパスの組。決定的なレイアウトのため、呼び出し側で
+  /// 安定した順序（例: ファイル名の昇順）に並べ替えてから渡すこと
+  pub attachment_payloads: &'a [(String, PathBuf)],
+}
+
+/// 指定された成果物一式を決定的な順序で1つのzipへまとめ、output_pathへ書き出す
+pub async fn write_bundle(output_path: &Path, input: BundleInput<'_>) -> Result<()> {
+  let mut file = File::create(output_path)
+    .await
+    .context("zipファイルの作成に失敗しました")?;
+  let mut writer = ZipFileWriter::with_tokio(&mut file);
+
+  write_file_entry(
+    &mut writer,
+    &format!("{}/result.csv", BUNDLE_ROOT_FOLDER),
+    input.result_csv_path,
+  )
+  .await?;
+
+  if let Some(junit_path) = input.junit_report_path {
+    write_file_entry(
+      &mut writer,
+      &format!("{}/junit-report.xml", BUNDLE_ROOT_FOLDER),
+      junit_path,
+    )
+    .await?;
+  }
+
+  write_bytes_entry(
+    &mut writer,
+    &format!("{}/summaries.json", BUNDLE_ROOT_FOLDER),
+    input.summaries_json.as_bytes(),
+  )
+  .await?;
+
+  for (entry_name, source_path) in input.attachment_payloads {
+    write_file_entry(
+      &mut writer,
+      &format!("{}/attachments/{}", BUNDLE_ROOT_FOLDER, entry_name),
+      source_path,
+    )
+    .await?;
+  }
+
+  writer.close().await.context("zipのクローズに失敗しました")?;
+  Ok(())
+}
+
+async fn write_file_entry(
+  writer: &mut ZipFileWriter<&mut File>,
+  entry_name: &str,
+  source_path: &Path,
+) -> Result<()> {
+  let mut source = File::open(source_path)
+    .await
+    .with_context(|| format!("バンドル対象ファイルの読み込みに失敗しました: {}", source_path.display()))?;
+  let mut bytes = Vec::new();
+  source.read_to_end(&mut bytes).await?;
+  write_bytes_entry(writer, entry_name, &bytes).await
+}
+
+async fn write_bytes_entry(
+  writer: &mut ZipFileWriter<&mut File>,
+  entry_name: &str,
+  bytes: &[u8],
+) -> Result<()> {
+  let builder = ZipEntryBuilder::new(entry_name.to_string().into(), Compression::Deflate);
+  writer
+    .write_entry_whole(builder, bytes)
+    .await
+    .with_context(|| format!("zipエントリの書き込みに失敗しました: {}", entry_name))?;
+  Ok(())
+}
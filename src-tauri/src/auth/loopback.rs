@@ -0,0 +1,140 @@
+// OAuthコールバックをローカルで受け取るループバックHTTPサーバー
+// リモートのCloudflare Worker経由のリダイレクトに依存せず、デスクトップアプリ単体でログインを完結させる
+use crate::auth::{verify_state, OAuthState, DEFAULT_STATE_MAX_AGE};
+use anyhow::{anyhow, Result};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 起動したループバックサーバーの情報（ポートは管理状態として保持し、認可URL生成時に参照する）
+#[derive(Debug, Clone, Copy)]
+pub struct LoopbackServer {
+  pub port: u16,
+}
+
+/// 127.0.0.1の空きポートにOAuthコールバック受信用サーバーを起動する
+pub fn start(app_handle: AppHandle) -> Result<LoopbackServer> {
+  let server = tiny_http::Server::http("127.0.0.1:0")
+    .map_err(|e| anyhow!("ループバックサーバーの起動に失敗しました: {}", e))?;
+  let port = server
+    .server_addr()
+    .to_ip()
+    .map(|addr| addr.port())
+    .ok_or_else(|| anyhow!("ループバックサーバーのポート取得に失敗しました"))?;
+
+  std::thread::spawn(move || {
+    for request in server.incoming_requests() {
+      handle_request(&app_handle, request);
+    }
+  });
+
+  log::info!("OAuthループバックサーバー起動: 127.0.0.1:{}", port);
+  Ok(LoopbackServer { port })
+}
+
+/// 1件のコールバックリクエストを処理し、stateを検証した上でフロントエンドへ`oauth://callback`を発火する
+fn handle_request(app_handle: &AppHandle, request: tiny_http::Request) {
+  let (code, state) = parse_callback_query(request.url());
+
+  let response_body = match (&code, &state) {
+    (Some(code), Some(state)) => match verify_state(state, DEFAULT_STATE_MAX_AGE) {
+      Ok(()) => {
+        let code_verifier = take_pending_verifier(app_handle);
+        let _ = app_handle.emit(
+          "oauth://callback",
+          serde_json::json!({ "code": code, "state": state, "code_verifier": code_verifier }),
+        );
+        "<html><body>認証が完了しました。このタブは閉じて構いません。</body></html>"
+      }
+      Err(e) => {
+        // 検証失敗時もcode_verifierを破棄し、リプレイに使い回されないようにする
+        let _ = take_pending_verifier(app_handle);
+        log::warn!("OAuthコールバック: stateの検証に失敗しました: {:?}", e);
+        "<html><body>認証に失敗しました（stateが不正または期限切れです）。</body></html>"
+      }
+    },
+    _ => "<html><body>認証パラメータが不足しています。</body></html>",
+  };
+
+  let response = tiny_http::Response::from_string(response_body).with_header(
+    tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap(),
+  );
+  let _ = request.respond(response);
+}
+
+/// Deep Link（`sfhsfiletrans://oauth/callback?code=...&state=...`）経由で受け取ったOAuthコールバックを
+/// ループバックサーバーと同じstate検証パスにかけ、成功時は同じ`oauth://callback`イベントを発火する
+/// （ネットワーク制限でループバックポートが使えない環境や、リモートworkerを使わない構成向けの代替経路）
+pub fn handle_deep_link_url(app_handle: &AppHandle, url: &str) {
+  if !url.starts_with("sfhsfiletrans://oauth/callback") {
+    return;
+  }
+
+  let (code, state) = parse_callback_query(url);
+  match (&code, &state) {
+    (Some(code), Some(state)) => match verify_state(state, DEFAULT_STATE_MAX_AGE) {
+      Ok(()) => {
+        let code_verifier = take_pending_verifier(app_handle);
+        let _ = app_handle.emit(
+          "oauth://callback",
+          serde_json::json!({ "code": code, "state": state, "code_verifier": code_verifier }),
+        );
+      }
+      Err(e) => {
+        // 検証失敗時もcode_verifierを破棄し、リプレイに使い回されないようにする
+        let _ = take_pending_verifier(app_handle);
+        log::warn!("Deep LinkのOAuthコールバック: stateの検証に失敗しました: {:?}", e);
+      }
+    },
+    _ => log::warn!("Deep LinkのOAuthコールバック: codeまたはstateが見つかりません"),
+  }
+}
+
+/// 保留中のPKCE code_verifierを取り出し、その場でクリアする（成功/失敗いずれの経路でも
+/// 一度取り出した時点でリプレイに使い回せないようにするため）
+fn take_pending_verifier(app_handle: &AppHandle) -> Option<String> {
+  app_handle
+    .state::<OAuthState>()
+    .pending_verifier
+    .lock()
+    .ok()
+    .and_then(|mut guard| guard.take())
+}
+
+/// `/oauth/callback?code=...&state=...`形式のクエリ文字列からcode/stateを取り出す
+fn parse_callback_query(url: &str) -> (Option<String>, Option<String>) {
+  let query = url.split('?').nth(1).unwrap_or("");
+  let mut code = None;
+  let mut state = None;
+
+  for pair in query.split('&') {
+    let mut parts = pair.splitn(2, '=');
+    let key = parts.next().unwrap_or("");
+    let value = parts.next().unwrap_or("");
+    let decoded = percent_decode(value);
+    match key {
+      "code" => code = Some(decoded),
+      "state" => state = Some(decoded),
+      _ => {}
+    }
+  }
+
+  (code, state)
+}
+
+/// 簡易パーセントデコード（OAuthのcode/stateは英数字と一部記号のみのため最小限の実装で足りる）
+fn percent_decode(value: &str) -> String {
+  let mut result = String::with_capacity(value.len());
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '+' => result.push(' '),
+      '%' => {
+        let hex: String = chars.by_ref().take(2).collect();
+        if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+          result.push(byte as char);
+        }
+      }
+      other => result.push(other),
+    }
+  }
+  result
+}
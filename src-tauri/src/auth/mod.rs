@@ -1,5 +1,10 @@
+pub mod loopback;
 pub mod oauth;
 pub mod storage;
 
-pub use oauth::{generate_auth_url, generate_state, OAuthState};
-pub use storage::{SecureStorage, StoredCredentials};
+pub use loopback::LoopbackServer;
+pub use oauth::{
+  generate_auth_url, generate_auth_url_with_pkce, generate_state, verify_state,
+  OAuthState, Scope, ScopeSet, StateVerifyError, DEFAULT_STATE_MAX_AGE,
+};
+pub use storage::{ProfileSummary, SecureStorage, StoredCredentials};
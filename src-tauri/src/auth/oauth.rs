@@ -1,46 +1,368 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
 use std::sync::Mutex;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// state不正/失効を区別するための検証エラー
+#[derive(Debug, PartialEq, Eq)]
+pub enum StateVerifyError {
+  /// フォーマットが不正、またはHMACが一致しない
+  Invalid,
+  /// `max_age`より古い
+  Expired,
+}
 
 pub struct OAuthState {
-  pub pending_auth: Mutex<Option<String>>,
-}
-
-pub fn generate_auth_url(client_id: &str, worker_url: &str, state: &str) -> String {
-  let scopes = vec![
-    "oauth",
-    "crm.objects.contacts.read",
-    "crm.objects.contacts.write",
-    "crm.objects.companies.read",
-    "crm.objects.companies.write",
-    "crm.objects.deals.read",
-    "crm.objects.deals.write",
-    "crm.objects.custom.read",
-    "crm.objects.custom.write",
-    "crm.schemas.contacts.read",
-    "crm.schemas.companies.read",
-    "crm.schemas.deals.read",
-    "crm.schemas.custom.read",
-    "tickets",
-    "files",
-  ];
+  /// PKCEのcode_verifier。認可コード横取りによるリプレイを防ぐため、
+  /// コールバックでのトークン交換まで保持しておく
+  /// （stateは自己検証可能なため、こちらと違いインメモリでの保持は不要）
+  pub pending_verifier: Mutex<Option<String>>,
+}
 
-  format!(
-    "https://app.hubspot.com/oauth/authorize?client_id={}&redirect_uri={}/oauth/callback&scope={}&state={}",
+/// ループバックポートが指定されていればローカルサーバーへ、無ければworker経由のredirect_uriを使う
+fn redirect_uri(worker_url: &str, loopback_port: Option<u16>) -> String {
+  match loopback_port {
+    Some(port) => format!("http://127.0.0.1:{}/oauth/callback", port),
+    None => format!("{}/oauth/callback", worker_url),
+  }
+}
+
+/// HubSpot APIスコープ。1つ1つがHubSpotの認可URLに渡す`scope`文字列に対応する
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+  Oauth,
+  ContactsRead,
+  ContactsWrite,
+  CompaniesRead,
+  CompaniesWrite,
+  DealsRead,
+  DealsWrite,
+  CustomRead,
+  CustomWrite,
+  ContactsSchemaRead,
+  CompaniesSchemaRead,
+  DealsSchemaRead,
+  CustomSchemaRead,
+  Tickets,
+  Files,
+}
+
+impl Scope {
+  fn as_str(&self) -> &'static str {
+    match self {
+      Scope::Oauth => "oauth",
+      Scope::ContactsRead => "crm.objects.contacts.read",
+      Scope::ContactsWrite => "crm.objects.contacts.write",
+      Scope::CompaniesRead => "crm.objects.companies.read",
+      Scope::CompaniesWrite => "crm.objects.companies.write",
+      Scope::DealsRead => "crm.objects.deals.read",
+      Scope::DealsWrite => "crm.objects.deals.write",
+      Scope::CustomRead => "crm.objects.custom.read",
+      Scope::CustomWrite => "crm.objects.custom.write",
+      Scope::ContactsSchemaRead => "crm.schemas.contacts.read",
+      Scope::CompaniesSchemaRead => "crm.schemas.companies.read",
+      Scope::DealsSchemaRead => "crm.schemas.deals.read",
+      Scope::CustomSchemaRead => "crm.schemas.custom.read",
+      Scope::Tickets => "tickets",
+      Scope::Files => "files",
+    }
+  }
+}
+
+/// 認可URLに載せるスコープの集合を組み立てるビルダー
+/// 機能ごとに必要最小限のスコープだけを要求し、過剰な書き込み権限を求めないようにする
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeSet {
+  scopes: Vec<Scope>,
+}
+
+impl ScopeSet {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 重複を除いてスコープを追加する
+  pub fn with(mut self, scope: Scope) -> Self {
+    if !self.scopes.contains(&scope) {
+      self.scopes.push(scope);
+    }
+    self
+  }
+
+  /// 読み取り専用プリセット（書き込み権限を一切要求しない）
+  pub fn read_only() -> Self {
+    Self {
+      scopes: vec![
+        Scope::Oauth,
+        Scope::ContactsRead,
+        Scope::CompaniesRead,
+        Scope::DealsRead,
+        Scope::CustomRead,
+        Scope::ContactsSchemaRead,
+        Scope::CompaniesSchemaRead,
+        Scope::DealsSchemaRead,
+        Scope::CustomSchemaRead,
+        Scope::Tickets,
+        Scope::Files,
+      ],
+    }
+  }
+
+  /// 連絡先のみ（読み取り・書き込み）のプリセット
+  pub fn contacts_only() -> Self {
+    Self {
+      scopes: vec![
+        Scope::Oauth,
+        Scope::ContactsRead,
+        Scope::ContactsWrite,
+        Scope::ContactsSchemaRead,
+      ],
+    }
+  }
+
+  /// 従来どおりの全件プリセット（全オブジェクトの読み取り・書き込み）
+  pub fn full() -> Self {
+    Self {
+      scopes: vec![
+        Scope::Oauth,
+        Scope::ContactsRead,
+        Scope::ContactsWrite,
+        Scope::CompaniesRead,
+        Scope::CompaniesWrite,
+        Scope::DealsRead,
+        Scope::DealsWrite,
+        Scope::CustomRead,
+        Scope::CustomWrite,
+        Scope::ContactsSchemaRead,
+        Scope::CompaniesSchemaRead,
+        Scope::DealsSchemaRead,
+        Scope::CustomSchemaRead,
+        Scope::Tickets,
+        Scope::Files,
+      ],
+    }
+  }
+
+  /// `StoredCredentials`への永続化や既存スコープとの比較に使う文字列表現の一覧
+  pub fn as_strings(&self) -> Vec<String> {
+    self.scopes.iter().map(|s| s.as_str().to_string()).collect()
+  }
+
+  /// 認可URLの`scope`クエリパラメータ値（スペース区切りをURLエンコードしたもの）を組み立てる
+  fn encode(&self) -> String {
+    self
+      .scopes
+      .iter()
+      .map(|s| s.as_str())
+      .collect::<Vec<_>>()
+      .join("%20")
+  }
+
+  /// `required`に含まれるスコープのうち、`granted`に無いものが1つでもあれば再同意が必要と判定する
+  /// 機能追加のたびに全スコープを要求するのではなく、不足分のみを検知してインクリメンタルに認可できるようにする
+  pub fn needs_reconsent(granted: &[String], required: &ScopeSet) -> bool {
+    required
+      .scopes
+      .iter()
+      .any(|scope| !granted.iter().any(|g| g == scope.as_str()))
+  }
+}
+
+fn build_auth_url(
+  client_id: &str,
+  worker_url: &str,
+  state: &str,
+  code_challenge: Option<&str>,
+  loopback_port: Option<u16>,
+  scopes: &ScopeSet,
+) -> String {
+  let mut url = format!(
+    "https://app.hubspot.com/oauth/authorize?client_id={}&redirect_uri={}&scope={}&state={}",
     client_id,
-    worker_url,
-    scopes.join("%20"),
+    redirect_uri(worker_url, loopback_port),
+    scopes.encode(),
     state
-  )
+  );
+
+  if let Some(challenge) = code_challenge {
+    url.push_str(&format!(
+      "&code_challenge={}&code_challenge_method=S256",
+      challenge
+    ));
+  }
+
+  url
 }
 
-pub fn generate_state() -> String {
+pub fn generate_auth_url(client_id: &str, worker_url: &str, state: &str, scopes: &ScopeSet) -> String {
+  build_auth_url(client_id, worker_url, state, None, None, scopes)
+}
+
+/// PKCE(S256)付きの認可URLを生成する。code_verifierは[A-Za-z0-9-._~]から成る43〜128文字で、
+/// code_challengeはBASE64URL(SHA256(ASCII(code_verifier)))（パディング無し）
+/// `loopback_port`を指定すると、redirect_uriがworker経由ではなく`http://127.0.0.1:<port>/oauth/callback`になる
+/// 戻り値は(認可URL, code_verifier)で、code_verifierはコールバックでのトークン交換時に送信する
+pub fn generate_auth_url_with_pkce(
+  client_id: &str,
+  worker_url: &str,
+  state: &str,
+  loopback_port: Option<u16>,
+  scopes: &ScopeSet,
+) -> (String, String) {
+  let code_verifier = generate_code_verifier();
+  let code_challenge = compute_code_challenge(&code_verifier);
+  let url = build_auth_url(
+    client_id,
+    worker_url,
+    state,
+    Some(&code_challenge),
+    loopback_port,
+    scopes,
+  );
+  (url, code_verifier)
+}
+
+fn generate_code_verifier() -> String {
   use rand::Rng;
-  const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+  const CHARSET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
   let mut rng = rand::thread_rng();
 
-  (0..32)
+  (0..128)
     .map(|_| {
       let idx = rng.gen_range(0..CHARSET.len());
       CHARSET[idx] as char
     })
     .collect()
 }
+
+fn compute_code_challenge(code_verifier: &str) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(code_verifier.as_bytes());
+  let digest = hasher.finalize();
+  base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest)
+}
+
+/// デフォルトのstate有効期限（10分）
+pub const DEFAULT_STATE_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// 自己検証可能なstateトークンを生成する
+/// `base64url(nonce(16byte) || issued_at_unix(8byte LE)) || "." || base64url(HMAC-SHA256(app_secret, nonce||issued_at))`
+/// の形式とし、改ざん検知と有効期限チェックをインメモリの状態に依存せず行えるようにする
+pub fn generate_state() -> String {
+  use rand::RngCore;
+
+  let mut nonce = [0u8; 16];
+  rand::thread_rng().fill_bytes(&mut nonce);
+  let issued_at = chrono::Utc::now().timestamp();
+
+  let mut payload = Vec::with_capacity(24);
+  payload.extend_from_slice(&nonce);
+  payload.extend_from_slice(&issued_at.to_le_bytes());
+
+  sign_payload(&payload)
+}
+
+/// `payload`にHMACを付与してstateトークン文字列を組み立てる
+fn sign_payload(payload: &[u8]) -> String {
+  let app_secret = super::storage::SecureStorage::get_or_create_app_secret()
+    .unwrap_or_else(|_| [0u8; 32]);
+
+  let mut mac =
+    HmacSha256::new_from_slice(&app_secret).expect("HMACキー長は可変長のため失敗しない");
+  mac.update(payload);
+  let signature = mac.finalize().into_bytes();
+
+  format!(
+    "{}.{}",
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(payload),
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(signature)
+  )
+}
+
+/// stateトークンのHMACと有効期限を検証する（インメモリの状態を持たないため再起動後も検証できる）
+pub fn verify_state(state: &str, max_age: Duration) -> Result<(), StateVerifyError> {
+  let (encoded_payload, encoded_signature) =
+    state.split_once('.').ok_or(StateVerifyError::Invalid)?;
+
+  let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+    .decode(encoded_payload)
+    .map_err(|_| StateVerifyError::Invalid)?;
+  let signature = base64::engine::general_purpose::URL_SAFE_NO_PAD
+    .decode(encoded_signature)
+    .map_err(|_| StateVerifyError::Invalid)?;
+
+  if payload.len() != 24 {
+    return Err(StateVerifyError::Invalid);
+  }
+
+  let app_secret =
+    super::storage::SecureStorage::get_or_create_app_secret().map_err(|_| StateVerifyError::Invalid)?;
+  let mut mac =
+    HmacSha256::new_from_slice(&app_secret).expect("HMACキー長は可変長のため失敗しない");
+  mac.update(&payload);
+  // `verify_slice`は内部で定数時間比較を行う
+  mac
+    .verify_slice(&signature)
+    .map_err(|_| StateVerifyError::Invalid)?;
+
+  let issued_at = i64::from_le_bytes(payload[16..24].try_into().unwrap());
+  let now = chrono::Utc::now().timestamp();
+  if now < issued_at || now - issued_at > max_age.as_secs() as i64 {
+    return Err(StateVerifyError::Expired);
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn verify_state_accepts_freshly_generated_token() {
+    let state = generate_state();
+    assert!(verify_state(&state, DEFAULT_STATE_MAX_AGE).is_ok());
+  }
+
+  #[test]
+  fn verify_state_rejects_malformed_token() {
+    assert_eq!(
+      verify_state("not-a-valid-token", DEFAULT_STATE_MAX_AGE),
+      Err(StateVerifyError::Invalid)
+    );
+  }
+
+  #[test]
+  fn verify_state_rejects_tampered_signature() {
+    let state = generate_state();
+    let (payload, _signature) = state.split_once('.').unwrap();
+    let tampered = format!("{}.{}", payload, "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+    assert_eq!(
+      verify_state(&tampered, DEFAULT_STATE_MAX_AGE),
+      Err(StateVerifyError::Invalid)
+    );
+  }
+
+  #[test]
+  fn verify_state_rejects_expired_token() {
+    use rand::RngCore;
+
+    let mut nonce = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let issued_at = chrono::Utc::now().timestamp() - DEFAULT_STATE_MAX_AGE.as_secs() as i64 - 60;
+
+    let mut payload = Vec::with_capacity(24);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&issued_at.to_le_bytes());
+
+    let state = sign_payload(&payload);
+    assert_eq!(
+      verify_state(&state, DEFAULT_STATE_MAX_AGE),
+      Err(StateVerifyError::Expired)
+    );
+  }
+}
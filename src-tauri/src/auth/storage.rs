@@ -1,40 +1,395 @@
-use anyhow::Result;
+use aes_gcm::aead::{Aead as AesAead, KeyInit as AesKeyInit};
+use aes_gcm::{Aes256Gcm, Nonce as AesNonce};
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use keyring::Entry;
+use rand::RngCore;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 const SERVICE_NAME: &str = "start-connect";
 const CREDENTIALS_KEY: &str = "credentials";
+const KEYRING_ENCRYPTION_KEY: &str = "credentials-encryption-key";
+const APP_SECRET_FILE: &str = "app_secret";
 
 const OAUTH_WORKER_URL: &str = match option_env!("OAUTH_WORKER_URL") {
   Some(url) => url,
   None => "https://hubspot-oauth-proxy.stlb-file-trans.workers.dev",
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+/// アクセストークン/リフレッシュトークンは`SecretString`で保持し、ログへの誤出力やメモリダンプでの
+/// 漏洩を防ぐ（Debug出力は自動的にマスクされ、ドロップ時にメモリがゼロ化される）
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StoredCredentials {
-  pub token: String,
-  pub refresh_token: Option<String>,
+  pub token: SecretString,
+  pub refresh_token: Option<SecretString>,
   pub expires_at: Option<i64>,
   pub portal_id: Option<u32>,
   pub ui_domain: Option<String>,
+  /// 同意済みのHubSpotスコープ文字列一覧。未取得の古い資格情報との互換のため未指定時は空になる
+  #[serde(default)]
+  pub granted_scopes: Vec<String>,
 }
 
-pub struct SecureStorage;
+impl std::fmt::Debug for StoredCredentials {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("StoredCredentials")
+      .field("token", &"[REDACTED]")
+      .field("refresh_token", &self.refresh_token.as_ref().map(|_| "[REDACTED]"))
+      .field("expires_at", &self.expires_at)
+      .field("portal_id", &self.portal_id)
+      .field("ui_domain", &self.ui_domain)
+      .field("granted_scopes", &self.granted_scopes)
+      .finish()
+  }
+}
+
+/// 他ポータルへ切り替える際に表示するプロファイル概要
+#[derive(Debug, Serialize)]
+pub struct ProfileSummary {
+  pub portal_id: u32,
+  pub ui_domain: String,
+  pub is_active: bool,
+}
+
+/// portal_idごとの資格情報とアクティブなポータルを保持する永続化表現
+/// 複数ポータルにまたがって作業するユーザーが再ログイン無しで切り替えられるようにする
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CredentialProfiles {
+  active_portal_id: Option<u32>,
+  profiles: HashMap<u32, StoredCredentials>,
+}
+
+/// 資格情報の永続化先を抽象化するトレイト
+/// OSキーチェーンが使えない環境（ヘッドレスLinux、CIなど）でもログインを維持できるようにする
+/// プロファイル全体をJSON文字列として出し入れするだけの薄い層にし、
+/// シリアライズの形式（単一資格情報 → 複数プロファイル）の変更をバックエンドに影響させない
+trait CredentialStore {
+  fn store_raw(&self, json: &str) -> Result<()>;
+  fn load_raw(&self) -> Result<String>;
+  fn clear_raw(&self) -> Result<()>;
+}
+
+/// OSキーチェーン（Secret Service / Keychain / Credential Manager）を使うバックエンド
+/// 一部のLinuxディストリビューションではSecret Serviceの実体がプレーンテキストのファイル
+/// （ロック無しのデフォルトキーリングなど）であるため、キーチェーンに渡す前にAES-256-GCMで
+/// 暗号化する。鍵は暗号文とは別のキーチェーンエントリに保持し、インストールごとにランダム生成する
+struct KeyringStore;
+
+impl KeyringStore {
+  /// 暗号化鍵をキーチェーンから読み込む。無ければランダムな256bit鍵を生成して保存する
+  fn load_or_create_key() -> Result<[u8; 32]> {
+    use base64::Engine;
+
+    let entry = Entry::new(SERVICE_NAME, KEYRING_ENCRYPTION_KEY)?;
+    if let Ok(encoded) = entry.get_password() {
+      let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+      return bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("暗号化鍵の長さが不正です"));
+    }
+
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    entry.set_password(&base64::engine::general_purpose::STANDARD.encode(key))?;
+    Ok(key)
+  }
+}
+
+impl CredentialStore for KeyringStore {
+  fn store_raw(&self, json: &str) -> Result<()> {
+    use base64::Engine;
+
+    let key = Self::load_or_create_key()?;
+    let cipher = Aes256Gcm::new((&key).into());
+
+    let mut nonce_bytes = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = AesNonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+      .encrypt(nonce, json.as_bytes())
+      .map_err(|e| anyhow::anyhow!("暗号化に失敗しました: {}", e))?;
+
+    // nonceを暗号文の先頭に連結してbase64化したものをキーチェーンの値とする
+    let mut stored = nonce_bytes.to_vec();
+    stored.extend_from_slice(&ciphertext);
 
-impl SecureStorage {
-  pub fn store_credentials(credentials: &StoredCredentials) -> Result<()> {
     let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY)?;
     let _ = entry.delete_credential();
-    let json = serde_json::to_string(credentials)?;
-    entry.set_password(&json)?;
+    entry.set_password(&base64::engine::general_purpose::STANDARD.encode(stored))?;
     Ok(())
   }
 
-  pub fn get_credentials() -> Result<StoredCredentials> {
+  fn load_raw(&self) -> Result<String> {
+    use base64::Engine;
+
     let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY)?;
-    let json = entry.get_password()?;
-    let credentials = serde_json::from_str(&json)?;
-    Ok(credentials)
+    let stored = base64::engine::general_purpose::STANDARD.decode(entry.get_password()?)?;
+
+    if stored.len() < 12 {
+      return Err(anyhow::anyhow!("キーチェーンの暗号化データの長さが不正です"));
+    }
+    let (nonce_bytes, ciphertext) = stored.split_at(12);
+
+    let key = Self::load_or_create_key()?;
+    let cipher = Aes256Gcm::new((&key).into());
+    let plaintext = cipher
+      .decrypt(AesNonce::from_slice(nonce_bytes), ciphertext)
+      .map_err(|e| anyhow::anyhow!("復号に失敗しました: {}", e))?;
+
+    Ok(String::from_utf8(plaintext)?)
+  }
+
+  fn clear_raw(&self) -> Result<()> {
+    log::debug!("Attempting to clear credentials from keychain");
+    let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY)?;
+    entry.delete_credential()?;
+    // 暗号化鍵も合わせて破棄し、万一キーチェーンの値が残っていても復号できないようにする
+    if let Ok(key_entry) = Entry::new(SERVICE_NAME, KEYRING_ENCRYPTION_KEY) {
+      let _ = key_entry.delete_credential();
+    }
+    Ok(())
+  }
+}
+
+/// 暗号化されたペイロードのオンディスク表現
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedPayload {
+  /// Argon2idのソルト（base64）
+  salt: String,
+  /// AEADのnonce（base64）
+  nonce: String,
+  /// 暗号文（base64）
+  ciphertext: String,
+}
+
+/// Argon2idで導出した鍵でXChaCha20-Poly1305暗号化したファイルに資格情報を保存するバックエンド
+/// Secret Serviceデーモンの無いヘッドレスLinux環境やCIでのフォールバックとして使う
+struct EncryptedFileStore {
+  /// 暗号化された認証情報ファイル
+  file_path: PathBuf,
+  /// 鍵導出用パスフレーズを保持するファイル（対話的なプロンプトが無いため端末ローカルに自動生成する）
+  key_path: PathBuf,
+}
+
+impl EncryptedFileStore {
+  fn new(app_data_dir: &Path) -> Self {
+    Self {
+      file_path: app_data_dir.join("credentials.enc"),
+      key_path: app_data_dir.join(".credential_key"),
+    }
+  }
+
+  /// パスフレーズを読み込む。無ければランダムなものを生成して保存する
+  fn load_or_create_passphrase(&self) -> Result<[u8; 32]> {
+    if let Some(parent) = self.key_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    if self.key_path.exists() {
+      let data = fs::read(&self.key_path)?;
+      data
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("パスフレーズファイルの長さが不正です"))
+    } else {
+      let mut passphrase = [0u8; 32];
+      rand::thread_rng().fill_bytes(&mut passphrase);
+      fs::write(&self.key_path, passphrase)?;
+      #[cfg(unix)]
+      {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&self.key_path, fs::Permissions::from_mode(0o600))?;
+      }
+      Ok(passphrase)
+    }
+  }
+
+  /// Argon2idでパスフレーズとソルトから32バイト鍵を導出する
+  fn derive_key(passphrase: &[u8], salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+      .hash_password_into(passphrase, salt, &mut key)
+      .map_err(|e| anyhow::anyhow!("鍵導出に失敗しました: {}", e))?;
+    Ok(key)
+  }
+}
+
+impl CredentialStore for EncryptedFileStore {
+  fn store_raw(&self, json: &str) -> Result<()> {
+    use base64::Engine;
+
+    let passphrase = self.load_or_create_passphrase()?;
+
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = Self::derive_key(&passphrase, &salt)?;
+
+    let mut nonce_bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    let ciphertext = cipher
+      .encrypt(nonce, json.as_bytes())
+      .map_err(|e| anyhow::anyhow!("暗号化に失敗しました: {}", e))?;
+
+    let payload = EncryptedPayload {
+      salt: base64::engine::general_purpose::STANDARD.encode(salt),
+      nonce: base64::engine::general_purpose::STANDARD.encode(nonce_bytes),
+      ciphertext: base64::engine::general_purpose::STANDARD.encode(ciphertext),
+    };
+
+    if let Some(parent) = self.file_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(&self.file_path, serde_json::to_string(&payload)?)?;
+    Ok(())
+  }
+
+  fn load_raw(&self) -> Result<String> {
+    use base64::Engine;
+
+    let data = fs::read_to_string(&self.file_path)
+      .with_context(|| format!("認証情報ファイルが見つかりません: {}", self.file_path.display()))?;
+    let payload: EncryptedPayload = serde_json::from_str(&data)?;
+
+    let salt = base64::engine::general_purpose::STANDARD.decode(payload.salt)?;
+    let nonce_bytes = base64::engine::general_purpose::STANDARD.decode(payload.nonce)?;
+    let ciphertext = base64::engine::general_purpose::STANDARD.decode(payload.ciphertext)?;
+
+    let passphrase = self.load_or_create_passphrase()?;
+    let key = Self::derive_key(&passphrase, &salt)?;
+
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    let nonce = XNonce::from_slice(&nonce_bytes);
+    let plaintext = cipher
+      .decrypt(nonce, ciphertext.as_ref())
+      .map_err(|e| anyhow::anyhow!("復号に失敗しました: {}", e))?;
+
+    Ok(String::from_utf8(plaintext)?)
+  }
+
+  fn clear_raw(&self) -> Result<()> {
+    let _ = fs::remove_file(&self.file_path);
+    let _ = fs::remove_file(&self.key_path);
+    Ok(())
+  }
+}
+
+/// アプリのデータディレクトリ（`<データディレクトリ>/start-connect`）
+fn app_data_dir() -> PathBuf {
+  dirs::data_dir()
+    .unwrap_or_else(std::env::temp_dir)
+    .join("start-connect")
+}
+
+/// keyringバックエンドが実際に利用可能かを軽量に確認する
+/// （Secret Serviceデーモンが存在しないヘッドレスLinuxではここで失敗する）
+fn keyring_is_available() -> bool {
+  match Entry::new(SERVICE_NAME, "availability-probe") {
+    Ok(entry) => {
+      let available = entry.set_password("probe").is_ok();
+      let _ = entry.delete_credential();
+      available
+    }
+    Err(_) => false,
+  }
+}
+
+/// 利用するバックエンドを選択する
+/// `SECURE_STORAGE_BACKEND=file|keyring` で明示的に指定しない限り、keyringが使える場合はそちらを優先する
+fn select_backend() -> Box<dyn CredentialStore> {
+  match std::env::var("SECURE_STORAGE_BACKEND").as_deref() {
+    Ok("file") => return Box::new(EncryptedFileStore::new(&app_data_dir())),
+    Ok("keyring") => return Box::new(KeyringStore),
+    _ => {}
+  }
+
+  if keyring_is_available() {
+    Box::new(KeyringStore)
+  } else {
+    log::info!("keyringバックエンドが利用できないため、暗号化ファイルにフォールバックします");
+    Box::new(EncryptedFileStore::new(&app_data_dir()))
+  }
+}
+
+impl CredentialProfiles {
+  /// 永続化されたプロファイル一覧を読み込む（何も保存されていなければ空の状態を返す）
+  fn load() -> Self {
+    select_backend()
+      .load_raw()
+      .ok()
+      .and_then(|json| serde_json::from_str(&json).ok())
+      .unwrap_or_default()
+  }
+
+  fn save(&self) -> Result<()> {
+    select_backend().store_raw(&serde_json::to_string(self)?)
+  }
+}
+
+pub struct SecureStorage;
+
+impl SecureStorage {
+  /// 認証済みポータルのプロファイルを作成または更新し、そのポータルをアクティブにする
+  /// 複数ポータルを行き来するユーザーがログインのたびに他ポータルの資格情報を失わないようにする
+  pub fn store_credentials(credentials: &StoredCredentials) -> Result<()> {
+    let portal_id = credentials
+      .portal_id
+      .ok_or_else(|| anyhow::anyhow!("portal_idが指定されていません"))?;
+
+    let mut profiles = CredentialProfiles::load();
+    profiles.profiles.insert(portal_id, credentials.clone());
+    profiles.active_portal_id = Some(portal_id);
+    profiles.save()
+  }
+
+  /// アクティブなプロファイルの認証情報を取得
+  pub fn get_credentials() -> Result<StoredCredentials> {
+    let profiles = CredentialProfiles::load();
+    let active_portal_id = profiles
+      .active_portal_id
+      .ok_or_else(|| anyhow::anyhow!("認証情報が見つかりません"))?;
+    profiles
+      .profiles
+      .get(&active_portal_id)
+      .cloned()
+      .ok_or_else(|| anyhow::anyhow!("アクティブなプロファイルの認証情報が見つかりません"))
+  }
+
+  /// 保存済みプロファイルの一覧を取得（切り替え先の選択肢を表示するため）
+  pub fn list_profiles() -> Result<Vec<ProfileSummary>> {
+    let profiles = CredentialProfiles::load();
+    let mut summaries: Vec<ProfileSummary> = profiles
+      .profiles
+      .iter()
+      .map(|(portal_id, credentials)| ProfileSummary {
+        portal_id: *portal_id,
+        ui_domain: credentials.ui_domain.clone().unwrap_or_default(),
+        is_active: profiles.active_portal_id == Some(*portal_id),
+      })
+      .collect();
+    summaries.sort_by_key(|p| p.portal_id);
+    Ok(summaries)
+  }
+
+  /// アクティブなプロファイルを切り替える（再ログインせずに別ポータルで作業する）
+  pub fn switch_profile(portal_id: u32) -> Result<()> {
+    let mut profiles = CredentialProfiles::load();
+    if !profiles.profiles.contains_key(&portal_id) {
+      return Err(anyhow::anyhow!(
+        "指定されたポータルのプロファイルが見つかりません: {}",
+        portal_id
+      ));
+    }
+    profiles.active_portal_id = Some(portal_id);
+    profiles.save()
   }
 
   /// 有効な認証情報を取得（期限切れの場合は自動リフレッシュ）
@@ -55,6 +410,23 @@ impl SecureStorage {
     Ok(credentials)
   }
 
+  /// アクティブな認証情報の有効期限が近ければリフレッシュする（バックグラウンドの定期リフレッシュタスク用）
+  /// リフレッシュを実行した場合は`true`を返す
+  pub async fn maybe_refresh() -> Result<bool> {
+    let credentials = Self::get_credentials()?;
+
+    if let Some(expires_at) = credentials.expires_at {
+      let now = chrono::Utc::now().timestamp();
+      // 5分のバッファを持たせてリフレッシュ
+      if now >= expires_at - 300 {
+        Self::refresh_token().await?;
+        return Ok(true);
+      }
+    }
+
+    Ok(false)
+  }
+
   /// トークンをリフレッシュ（内部関数）
   async fn refresh_token() -> Result<()> {
     let credentials = Self::get_credentials()?;
@@ -73,7 +445,7 @@ impl SecureStorage {
     let client = reqwest::Client::new();
     let response = client
       .post(format!("{}/oauth/refresh", OAUTH_WORKER_URL))
-      .json(&serde_json::json!({ "refresh_token": refresh_token }))
+      .json(&serde_json::json!({ "refresh_token": refresh_token.expose_secret() }))
       .send()
       .await?;
 
@@ -88,11 +460,13 @@ impl SecureStorage {
 
     let expires_at = chrono::Utc::now().timestamp() + token_data.expires_in as i64;
     let new_credentials = StoredCredentials {
-      token: token_data.access_token,
-      refresh_token: Some(token_data.refresh_token),
+      token: token_data.access_token.into(),
+      refresh_token: Some(token_data.refresh_token.into()),
       expires_at: Some(expires_at),
       portal_id: Some(portal_id),
       ui_domain: Some(ui_domain),
+      // リフレッシュではスコープは変わらないため、同意済みスコープはそのまま引き継ぐ
+      granted_scopes: credentials.granted_scopes,
     };
 
     Self::store_credentials(&new_credentials)?;
@@ -100,13 +474,93 @@ impl SecureStorage {
     Ok(())
   }
 
+  /// 保存済みアカウント（ポータル）の一覧を取得。`list_profiles`の別名
+  pub fn list_accounts() -> Result<Vec<ProfileSummary>> {
+    Self::list_profiles()
+  }
+
+  /// 指定ポータルの認証情報を取得する（アクティブかどうかに関わらず）
+  pub fn get(portal_id: u32) -> Result<StoredCredentials> {
+    let profiles = CredentialProfiles::load();
+    profiles
+      .profiles
+      .get(&portal_id)
+      .cloned()
+      .ok_or_else(|| anyhow::anyhow!("指定されたポータルの認証情報が見つかりません: {}", portal_id))
+  }
+
+  /// アクティブなポータルを切り替える。`switch_profile`の別名
+  pub fn set_active(portal_id: u32) -> Result<()> {
+    Self::switch_profile(portal_id)
+  }
+
+  /// 指定ポータルのプロファイルを削除する。削除対象がアクティブだった場合は
+  /// 残っている別のプロファイルへ自動的に切り替える
+  pub fn remove(portal_id: u32) -> Result<()> {
+    let mut profiles = CredentialProfiles::load();
+    profiles.profiles.remove(&portal_id);
+
+    if profiles.active_portal_id == Some(portal_id) {
+      profiles.active_portal_id = profiles.profiles.keys().next().copied();
+    }
+
+    if profiles.profiles.is_empty() {
+      select_backend().clear_raw()
+    } else {
+      profiles.save()
+    }
+  }
+
+  /// 指定ポータルの同意済みスコープが`required`を満たしているか確認し、不足があれば再同意が必要と判定する
+  /// 機能追加時にいきなり全スコープを要求するのではなく、不足分のみをインクリメンタルに認可させるために使う
+  pub fn needs_reconsent(portal_id: u32, required: &super::ScopeSet) -> Result<bool> {
+    let credentials = Self::get(portal_id)?;
+    Ok(super::ScopeSet::needs_reconsent(
+      &credentials.granted_scopes,
+      required,
+    ))
+  }
+
+  /// OAuth state署名用のインストール固有アプリシークレットを取得する。無ければランダム生成して永続化する
+  pub fn get_or_create_app_secret() -> Result<[u8; 32]> {
+    let path = app_data_dir().join(APP_SECRET_FILE);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    if path.exists() {
+      let data = fs::read(&path)?;
+      data
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("アプリシークレットファイルの長さが不正です"))
+    } else {
+      let mut secret = [0u8; 32];
+      rand::thread_rng().fill_bytes(&mut secret);
+      fs::write(&path, secret)?;
+      #[cfg(unix)]
+      {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+      }
+      Ok(secret)
+    }
+  }
+
+  /// アクティブなプロファイルをログアウトする
+  /// 他にプロファイルが残っていれば、そのうちの1つを新たにアクティブにする
   pub fn clear_credentials() -> Result<()> {
-    log::debug!("Attempting to clear credentials from keychain");
-    let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY)?;
-    log::debug!("Entry created, deleting credential...");
-    entry.delete_credential()?;
-    log::debug!("Credential deleted successfully");
-    Ok(())
+    let mut profiles = CredentialProfiles::load();
+
+    if let Some(active_portal_id) = profiles.active_portal_id.take() {
+      profiles.profiles.remove(&active_portal_id);
+    }
+    profiles.active_portal_id = profiles.profiles.keys().next().copied();
+
+    if profiles.profiles.is_empty() {
+      select_backend().clear_raw()
+    } else {
+      profiles.save()
+    }
   }
 }
 
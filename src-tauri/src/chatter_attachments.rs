@@ -0,0 +1,97 @@
+// Chatter(FeedItem/FeedComment)に紐づく添付ファイルをContentVersion情報から実体化するモジュール
+//
+// このリポジトリはSalesforceへのライブAPI接続を持たず、エクスポート済みのContentVersion.csv
+// （またはローカルのContentVersionフォルダ）のみを扱う。そのため「解決」は、既に読み込んだ
+// FileInfoをタイトル/MIMEタイプ/サイズを含む添付ファイル情報へ変換する処理として実装する
+use crate::csv::processor::FileInfo;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+
+const DEFAULT_PREFETCH_CONCURRENCY: usize = 4;
+
+/// 実体化された添付ファイル情報
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MaterializedAttachment {
+  pub content_document_id: String,
+  pub title: String,
+  pub mime_type: String,
+  /// eagerモードで事前取得できた場合のみ推定サイズを持つ
+  pub size_bytes: Option<u64>,
+  /// eagerモードで事前取得したbase64データ。lazyモードでは常にNone
+  pub prefetched_base64: Option<String>,
+}
+
+/// ファイル名の拡張子からMIMEタイプを推定する
+fn guess_mime_type(filename: &str) -> String {
+  let ext = filename.rsplit('.').next().unwrap_or("").to_lowercase();
+  match ext.as_str() {
+    "pdf" => "application/pdf",
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "txt" => "text/plain",
+    "csv" => "text/csv",
+    "doc" => "application/msword",
+    "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "xls" => "application/vnd.ms-excel",
+    "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    _ => "application/octet-stream",
+  }
+  .to_string()
+}
+
+fn to_materialized(
+  content_document_id: &str,
+  info: &FileInfo,
+  prefetched_base64: Option<String>,
+) -> MaterializedAttachment {
+  MaterializedAttachment {
+    content_document_id: content_document_id.to_string(),
+    title: info.path_on_client.clone(),
+    mime_type: guess_mime_type(&info.path_on_client),
+    size_bytes: prefetched_base64
+      .as_ref()
+      .map(|base64_data| (base64_data.len() as u64 * 3) / 4),
+    prefetched_base64,
+  }
+}
+
+/// 添付ContentDocumentIdをFileInfoから解決する（遅延モード: バイト列はここでは読み込まない）
+pub fn resolve_lazy(
+  content_document_ids: &[String],
+  file_info: &HashMap<String, FileInfo>,
+) -> Vec<MaterializedAttachment> {
+  content_document_ids
+    .iter()
+    .filter_map(|id| file_info.get(id).map(|info| to_materialized(id, info, None)))
+    .collect()
+}
+
+/// 添付ContentDocumentIdをFileInfoから解決し、指定の並行数でバイト列を事前取得する（eagerモード）
+/// 1件の読み込み失敗が他の添付ファイルへ波及しないよう、エラーは個別に隔離してログへ残すのみとする
+pub async fn resolve_eager(
+  content_document_ids: &[String],
+  file_info: &HashMap<String, FileInfo>,
+  concurrency: Option<usize>,
+) -> Vec<MaterializedAttachment> {
+  let concurrency = concurrency.unwrap_or(DEFAULT_PREFETCH_CONCURRENCY).max(1);
+
+  let targets: Vec<(String, FileInfo)> = content_document_ids
+    .iter()
+    .filter_map(|id| file_info.get(id).map(|info| (id.clone(), info.clone())))
+    .collect();
+
+  stream::iter(targets.into_iter().map(|(id, info)| async move {
+    match info.version_data.as_ref().map(|version_data| version_data.to_base64()) {
+      Some(Ok(base64_data)) => to_materialized(&id, &info, Some(base64_data)),
+      Some(Err(e)) => {
+        log::warn!("添付ファイル事前取得失敗 {}: {}", id, e);
+        to_materialized(&id, &info, None)
+      }
+      None => to_materialized(&id, &info, None),
+    }
+  }))
+  .buffer_unordered(concurrency)
+  .collect()
+  .await
+}
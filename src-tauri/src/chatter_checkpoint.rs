@@ -0,0 +1,84 @@
+// Chatter移行の再開用チェックポイントを管理するモジュール
+// 親レコード（salesforce_id）ごとに、これまでに同期済みの最新FeedItem.CreatedDateを記録し、
+// 途中で中断した移行を再実行した際に同じFeedItemを重複投稿しないようにする
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+const CHECKPOINT_FILE_NAME: &str = "chatter_checkpoint.json";
+
+/// 親レコードごとの同期済み最新CreatedDate
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChatterCheckpointData {
+  /// salesforce_id(ParentId) -> 同期済みの最新FeedItem.CreatedDate
+  last_synced_created_date: HashMap<String, String>,
+}
+
+/// Chatter移行の再開用チェックポイント
+pub struct ChatterCheckpoint {
+  path: PathBuf,
+  data: ChatterCheckpointData,
+}
+
+impl ChatterCheckpoint {
+  fn checkpoint_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let base = app
+      .path()
+      .app_data_dir()
+      .context("アプリデータディレクトリの取得に失敗しました")?;
+    fs::create_dir_all(&base)?;
+    Ok(base.join(CHECKPOINT_FILE_NAME))
+  }
+
+  /// チェックポイントを読み込む。ファイルが無い場合や壊れている場合は
+  /// 「チェックポイント無し（全件未同期）」として扱う
+  pub fn load(app: &tauri::AppHandle) -> Result<Self> {
+    let path = Self::checkpoint_path(app)?;
+    let data = fs::read_to_string(&path)
+      .ok()
+      .and_then(|raw| serde_json::from_str(&raw).ok())
+      .unwrap_or_default();
+
+    log::info!(
+      "Chatterチェックポイント読み込み: {}件の親レコード",
+      data.last_synced_created_date.len()
+    );
+
+    Ok(Self { path, data })
+  }
+
+  /// チェックポイントに記録済みの親レコード数（再開時に進捗表示へ使う）
+  pub fn restored_count(&self) -> usize {
+    self.data.last_synced_created_date.len()
+  }
+
+  /// このFeedItemが前回の同期済み範囲より新しいかどうか
+  /// （チェックポイントに記録の無い親は常に未同期扱い）
+  pub fn is_new(&self, salesforce_id: &str, created_date: &str) -> bool {
+    match self.data.last_synced_created_date.get(salesforce_id) {
+      Some(last_synced) => created_date > last_synced.as_str(),
+      None => true,
+    }
+  }
+
+  /// 親レコードの同期済み最新CreatedDateを更新する（既存の値より古い場合は後退させない）
+  pub fn advance(&mut self, salesforce_id: &str, created_date: &str) {
+    let entry = self
+      .data
+      .last_synced_created_date
+      .entry(salesforce_id.to_string())
+      .or_default();
+    if created_date > entry.as_str() {
+      *entry = created_date.to_string();
+    }
+  }
+
+  /// チェックポイントをディスクへ保存する
+  pub fn save(&self) -> Result<()> {
+    fs::write(&self.path, serde_json::to_string(&self.data)?)?;
+    Ok(())
+  }
+}
@@ -1,9 +1,22 @@
 // OAuth関連のTauriコマンド
-use crate::auth::{generate_auth_url, generate_state, OAuthState, SecureStorage};
+use crate::auth::{
+  generate_auth_url_with_pkce, generate_state, LoopbackServer, OAuthState, ScopeSet,
+  SecureStorage,
+};
 use anyhow::Result;
+use secrecy::ExposeSecret;
 use serde::Serialize;
 use tauri::{command, State};
 
+/// プリセット名からスコープ集合を解決する。未指定または不明な名前の場合は`full`（従来どおりの挙動）とする
+fn resolve_scope_preset(preset: Option<&str>) -> ScopeSet {
+  match preset {
+    Some("read_only") => ScopeSet::read_only(),
+    Some("contacts_only") => ScopeSet::contacts_only(),
+    _ => ScopeSet::full(),
+  }
+}
+
 /// ポータル情報
 #[derive(Debug, Serialize)]
 pub struct PortalInfo {
@@ -31,14 +44,31 @@ fn get_worker_url() -> String {
 }
 
 /// OAuth認証を開始
+/// `scope_preset`は`"read_only"` / `"contacts_only"` / `"full"`のいずれか（未指定時は`"full"`）で、
+/// 機能に必要な分だけを要求し過剰な書き込み権限を求めないようにする
 #[command]
-pub async fn start_oauth_flow(oauth_state: State<'_, OAuthState>) -> Result<String, String> {
+pub async fn start_oauth_flow(
+  oauth_state: State<'_, OAuthState>,
+  loopback_server: State<'_, LoopbackServer>,
+  scope_preset: Option<String>,
+) -> Result<String, String> {
   let state = generate_state();
+  let scopes = resolve_scope_preset(scope_preset.as_deref());
 
-  // state を保存
-  *oauth_state.pending_auth.lock().map_err(|e| e.to_string())? = Some(state.clone());
+  let (auth_url, code_verifier) = generate_auth_url_with_pkce(
+    &get_client_id(),
+    &get_worker_url(),
+    &state,
+    Some(loopback_server.port),
+    &scopes,
+  );
 
-  let auth_url = generate_auth_url(&get_client_id(), &get_worker_url(), &state);
+  // PKCEのcode_verifierをコールバックでのトークン交換まで保存
+  // （stateは自己検証可能なため、インメモリでの保持は不要）
+  *oauth_state
+    .pending_verifier
+    .lock()
+    .map_err(|e| e.to_string())? = Some(code_verifier);
 
   Ok(auth_url)
 }
@@ -69,21 +99,59 @@ pub async fn save_oauth_tokens(
   expires_in: u64,
   portal_id: u32,
   ui_domain: String,
+  granted_scopes: Vec<String>,
 ) -> Result<(), String> {
   let expires_at = chrono::Utc::now().timestamp() + expires_in as i64;
 
   let credentials = crate::auth::StoredCredentials {
-    token: access_token,
-    refresh_token: Some(refresh_token),
+    token: access_token.into(),
+    refresh_token: Some(refresh_token.into()),
     expires_at: Some(expires_at),
     portal_id: Some(portal_id),
     ui_domain: Some(ui_domain),
+    granted_scopes,
   };
 
   SecureStorage::store_credentials(&credentials).map_err(|e| e.to_string())?;
   Ok(())
 }
 
+/// 指定ポータルの同意済みスコープが`scope_preset`で要求される範囲を満たしているか確認する
+/// 満たしていなければ、フロントエンドはインクリメンタル認可（`start_oauth_flow`の再実行）を促す
+#[command]
+pub async fn needs_reconsent(portal_id: u32, scope_preset: String) -> Result<bool, String> {
+  let required = resolve_scope_preset(Some(&scope_preset));
+  SecureStorage::needs_reconsent(portal_id, &required).map_err(|e| e.to_string())
+}
+
+/// 保存済みのポータルプロファイル一覧を取得
+#[command]
+pub async fn list_profiles() -> Result<Vec<crate::auth::ProfileSummary>, String> {
+  SecureStorage::list_profiles().map_err(|e| e.to_string())
+}
+
+/// アクティブなポータルプロファイルを切り替える
+#[command]
+pub async fn switch_profile(portal_id: u32) -> Result<(), String> {
+  SecureStorage::switch_profile(portal_id).map_err(|e| e.to_string())
+}
+
+/// 指定ポータルのプロファイルを削除する
+#[command]
+pub async fn remove_profile(portal_id: u32) -> Result<(), String> {
+  SecureStorage::remove(portal_id).map_err(|e| e.to_string())
+}
+
+/// 有効なアクセストークンを保証して返す（期限が近ければ内部でリフレッシュしてから返す）
+/// CRM呼び出しの前にフロントエンドがawaitする想定
+#[command]
+pub async fn ensure_valid_token() -> Result<String, String> {
+  let credentials = SecureStorage::get_credentials_with_refresh()
+    .await
+    .map_err(|e| e.to_string())?;
+  Ok(credentials.token.expose_secret().to_string())
+}
+
 /// ログアウト
 #[command]
 pub async fn logout() -> Result<(), String> {
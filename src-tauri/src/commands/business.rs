@@ -2,13 +2,26 @@
 // フロントエンドから呼び出し可能なRust関数を定義
 
 use crate::auth::SecureStorage;
+use crate::chatter_attachments;
+use crate::chatter_checkpoint::ChatterCheckpoint;
 use crate::csv::{CsvProcessor, ObjectMapping};
 use crate::hubspot::{build_record_url, HubSpotService};
+use crate::journal::{RunEvent, RunJournal};
+use crate::junit_report::write_junit_report;
+use crate::mqtt_sink;
+use crate::note_ledger::NoteLedger;
+use crate::upload_index::{compute_content_hash, UploadIndex};
 use anyhow::Result;
-use serde::Serialize;
+use futures::stream::{self, StreamExt};
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
-use tauri::{command, Emitter};
+use tauri::{command, Emitter, Manager};
+
+/// 同時アップロード数のデフォルト値（HubSpotのレート制限を踏まえた控えめな値）
+const DEFAULT_UPLOAD_CONCURRENCY: usize = 8;
 
 /// ファイルマッピング処理のレスポンスデータ
 #[derive(Debug, Serialize)]
@@ -17,6 +30,94 @@ pub struct FileMappingResponse {
   pub result_csv_path: String,
   /// オブジェクトごとのサマリー
   pub summaries: Vec<ObjectSummary>,
+  /// レコードごとの処理結果（監査・再試行の判断材料にするため全件保持する）
+  pub records: Vec<RecordResult>,
+  /// `cancel_migration`により途中で中断されたか（trueの場合、一部レコードは"cancelled"ステータスとなる）
+  pub cancelled: bool,
+  /// アップロード前の事前検証で検出された問題のある添付ファイルの件数サマリー
+  pub preflight: PreflightReport,
+  /// CIが移行結果をテスト結果として読み取れるよう書き出すJUnit形式のXMLレポートパス（一時ファイル）
+  pub junit_report_path: String,
+  /// 結果CSV・JUnitレポート・サマリーJSONを1つにまとめたzipアーカイブのパス（一時ファイル。作成に失敗した場合は空文字）
+  pub bundle_path: String,
+}
+
+/// アップロード前の事前検証で検出された問題のある添付ファイルの件数サマリー
+/// （Chatter移行では別経路で添付ファイルを解決するため、このスキャンは対象外＝常に0件）
+#[derive(Debug, Default, Serialize)]
+pub struct PreflightReport {
+  /// バイトサイズが0のファイル
+  pub zero_byte_files: usize,
+  /// VersionDataが欠落またはCSV上で空のファイル
+  pub missing_data_files: usize,
+  /// base64デコードまたはディスクからの読み込みに失敗したファイル
+  pub unreadable_files: usize,
+  /// HubSpotがブロックする、または許可されていない拡張子のファイル
+  pub unsupported_extension_files: usize,
+}
+
+impl PreflightReport {
+  fn total(&self) -> usize {
+    self.zero_byte_files
+      + self.missing_data_files
+      + self.unreadable_files
+      + self.unsupported_extension_files
+  }
+}
+
+/// レコード単位の処理結果
+/// CSVへの出力内容と対応しており、export_last_reportでCSV/JSONとして書き出せる
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordResult {
+  pub salesforce_id: String,
+  pub hubspot_object: String,
+  pub hubspot_record_id: String,
+  pub record_url: String,
+  pub files_uploaded: usize,
+  pub files_skipped: usize,
+  /// コンテンツハッシュが一致し、再アップロードを省略したファイル数
+  pub deduplicated_files: usize,
+  /// アップロードまたは流用したファイルのコンテンツハッシュ（セミコロン区切り、監査用）
+  pub content_hashes: String,
+  /// 送信元バイト列の合計サイズ（整合性検証用。新規アップロードが無い場合は0）
+  pub bytes_expected: u64,
+  /// アップロード後にHubSpotへ再問い合わせして得た報告サイズの合計（整合性検証用）
+  pub bytes_stored: u64,
+  pub note_created: bool,
+  pub error: Option<String>,
+}
+
+/// 直近の実行結果レジャーを保持するTauri管理ステート
+/// export_last_reportから参照できるようにプロセスの寿命だけメモリに持つ
+#[derive(Default)]
+pub struct ReportState {
+  pub last_report: Mutex<Option<Vec<RecordResult>>>,
+}
+
+/// 実行中のファイルマッピング/Chatter移行処理へのキャンセル要求を保持するTauri管理ステート
+/// 同時に複数の移行は実行されない前提のため、プロセス全体で単一のフラグを共有する
+pub struct CancellationState {
+  pub cancelled: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl Default for CancellationState {
+  fn default() -> Self {
+    Self {
+      cancelled: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+    }
+  }
+}
+
+/// 実行中のファイルマッピング/Chatter移行処理にキャンセルを要求する
+/// 次のレコード境界、またはファイルアップロードの合間でチェックされ、それまでに完了した分は結果に保持される
+#[command]
+pub async fn cancel_migration(
+  cancellation_state: tauri::State<'_, CancellationState>,
+) -> Result<(), String> {
+  cancellation_state
+    .cancelled
+    .store(true, std::sync::atomic::Ordering::SeqCst);
+  Ok(())
 }
 
 /// オブジェクトごとの処理サマリー
@@ -34,6 +135,8 @@ pub struct ObjectSummary {
   pub error_count: usize,
   /// アップロードされたファイル数
   pub uploaded_files: usize,
+  /// コンテンツハッシュが一致し、再アップロードを省略した（既存のHubSpotファイルを再利用した）ファイル数
+  pub deduplicated_files: usize,
 }
 
 /// オブジェクト分析結果
@@ -50,6 +153,25 @@ pub struct HubSpotObject {
   pub label: String,
 }
 
+/// アップロードスループット計測レポート
+#[derive(Debug, Serialize)]
+pub struct BenchmarkReport {
+  /// アップロードしたサンプルファイル数
+  pub sample_count: usize,
+  /// アップロードした合計バイト数
+  pub total_bytes: u64,
+  /// 計測にかかった合計時間（ミリ秒）
+  pub elapsed_ms: u128,
+  /// 1秒あたりのファイル数
+  pub files_per_sec: f64,
+  /// 1秒あたりのバイト数
+  pub bytes_per_sec: f64,
+  /// レイテンシの中央値（ミリ秒）
+  pub p50_latency_ms: f64,
+  /// レイテンシの95パーセンタイル（ミリ秒）
+  pub p95_latency_ms: f64,
+}
+
 /// 進捗情報
 #[derive(Debug, Serialize, Clone)]
 pub struct ProgressInfo {
@@ -59,31 +181,351 @@ pub struct ProgressInfo {
   pub progress: u8,
   /// 詳細メッセージ
   pub message: String,
+  /// 現在のステージ番号（1始まり）
+  pub current_stage: u8,
+  /// ステージの総数
+  pub total_stages: u8,
+  /// 処理済み件数（レコードまたはファイル）
+  pub items_processed: usize,
+  /// 処理対象の総件数（0の場合はまだ件数が確定していない、または件数ベースの進捗ではないステージ）
+  pub items_total: usize,
+  /// 推定残り時間（秒）。件数が確定しておらずスループットを算出できない場合はNone
+  pub estimated_seconds_remaining: Option<u64>,
+}
+
+/// `step`がステージ一覧の何番目かを1始まりで返す。一覧に無いステップ（cache_restoreなどの付随的な通知）は0を返す
+fn stage_index(stages: &[&str], step: &str) -> u8 {
+  stages
+    .iter()
+    .position(|&s| s == step)
+    .map(|i| (i + 1) as u8)
+    .unwrap_or(0)
+}
+
+/// 経過時間と処理済み件数から残り時間を推定する（開始からの累積の平均スループットに基づく概算値。
+/// 直近の区間だけを対象にした移動平均は取らない）
+fn estimate_seconds_remaining(
+  start: std::time::Instant,
+  items_processed: usize,
+  items_total: usize,
+) -> Option<u64> {
+  if items_processed == 0 || items_total <= items_processed {
+    return None;
+  }
+  let elapsed_secs = start.elapsed().as_secs_f64();
+  if elapsed_secs <= 0.0 {
+    return None;
+  }
+  let throughput = items_processed as f64 / elapsed_secs;
+  if throughput <= 0.0 {
+    return None;
+  }
+  let remaining_items = (items_total - items_processed) as f64;
+  Some((remaining_items / throughput).round() as u64)
 }
 
 /// ファイルマッピング処理のメインコマンド
 /// Salesforce CSVファイルを処理してHubSpotにファイルをアップロード・ノート作成
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn process_file_mapping(
   content_version_path: String,
   content_document_link_path: String,
   content_version_folder_path: String,
   object_mappings: HashMap<String, ObjectMapping>,
+  concurrency: Option<usize>,
+  dry_run: Option<bool>,
+  file_backend: Option<crate::file_backend::FileBackendConfig>,
+  window: tauri::Window,
+  report_state: tauri::State<'_, ReportState>,
+) -> Result<FileMappingResponse, String> {
+  let run_id = RunJournal::derive_run_id(&[
+    &content_version_path,
+    &content_document_link_path,
+    &content_version_folder_path,
+  ]);
+  let response = run_file_mapping(
+    run_id,
+    content_version_path,
+    content_document_link_path,
+    content_version_folder_path,
+    object_mappings,
+    concurrency,
+    dry_run.unwrap_or(false),
+    file_backend,
+    window,
+  )
+  .await?;
+  *report_state.last_report.lock().unwrap() = Some(response.records.clone());
+  Ok(response)
+}
+
+/// 中断した実行をrun_idから再開する
+/// 入力ファイルは同一のものを指定する必要がある（ジャーナルは完了済みレコードのみを記憶している）
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn resume_file_mapping(
+  run_id: String,
+  content_version_path: String,
+  content_document_link_path: String,
+  content_version_folder_path: String,
+  object_mappings: HashMap<String, ObjectMapping>,
+  concurrency: Option<usize>,
+  file_backend: Option<crate::file_backend::FileBackendConfig>,
+  window: tauri::Window,
+  report_state: tauri::State<'_, ReportState>,
+) -> Result<FileMappingResponse, String> {
+  let response = run_file_mapping(
+    run_id,
+    content_version_path,
+    content_document_link_path,
+    content_version_folder_path,
+    object_mappings,
+    concurrency,
+    false,
+    file_backend,
+    window,
+  )
+  .await?;
+  *report_state.last_report.lock().unwrap() = Some(response.records.clone());
+  Ok(response)
+}
+
+/// 直近の処理結果レジャーをCSVまたはJSONとしてファイルに書き出す
+/// `format` には "csv" または "json" を指定する
+#[command]
+pub async fn export_last_report(
+  path: String,
+  format: String,
+  report_state: tauri::State<'_, ReportState>,
+) -> Result<(), String> {
+  let records = report_state
+    .last_report
+    .lock()
+    .unwrap()
+    .clone()
+    .ok_or_else(|| "エクスポート可能な処理結果がありません".to_string())?;
+
+  match format.to_lowercase().as_str() {
+    "json" => {
+      let json = serde_json::to_string_pretty(&records)
+        .map_err(|e| format!("JSONシリアライズエラー: {}", e))?;
+      std::fs::write(&path, json).map_err(|e| format!("レポート書き込みエラー: {}", e))?;
+    }
+    "csv" => {
+      let mut writer =
+        csv::Writer::from_path(&path).map_err(|e| format!("CSVファイル作成エラー: {}", e))?;
+      writer
+        .write_record([
+          "Salesforce ID",
+          "HubSpot Object",
+          "HubSpot Record ID",
+          "HubSpot Record URL",
+          "Files Uploaded",
+          "Files Skipped",
+          "Deduplicated Files",
+          "Content Hashes",
+          "Bytes Expected",
+          "Bytes Stored",
+          "Note Created",
+          "Error",
+        ])
+        .map_err(|e| format!("CSVヘッダー書き込みエラー: {}", e))?;
+      for record in &records {
+        let _ = writer.write_record([
+          &record.salesforce_id,
+          &record.hubspot_object,
+          &record.hubspot_record_id,
+          &record.record_url,
+          &record.files_uploaded.to_string(),
+          &record.files_skipped.to_string(),
+          &record.deduplicated_files.to_string(),
+          &record.content_hashes,
+          &record.bytes_expected.to_string(),
+          &record.bytes_stored.to_string(),
+          &record.note_created.to_string(),
+          record.error.as_deref().unwrap_or(""),
+        ]);
+      }
+      writer
+        .flush()
+        .map_err(|e| format!("CSVフラッシュエラー: {}", e))?;
+    }
+    other => return Err(format!("サポートされていない形式です: {}", other)),
+  }
+
+  log::info!("処理結果レポートを書き出し: {} ({})", path, format);
+  Ok(())
+}
+
+/// 未完了のままジャーナルが残っている実行の一覧を取得する
+#[command]
+pub async fn list_incomplete_runs(
+  app_handle: tauri::AppHandle,
+) -> Result<Vec<crate::journal::IncompleteRun>, String> {
+  crate::journal::list_incomplete_runs(&app_handle).map_err(|e| e.to_string())
+}
+
+/// HubSpotがアップロードをブロックする拡張子（抜粋）。実行ファイルやスクリプトなど、
+/// マルウェア配布に悪用されやすい形式を対象とする
+const BLOCKED_FILE_EXTENSIONS: &[&str] = &[
+  "exe", "bat", "cmd", "com", "cpl", "msi", "scr", "vbs", "js", "jar", "dll", "ps1", "sh", "app",
+  "gadget", "hta", "jse", "lnk", "msc", "msp", "pif", "reg", "vb", "vbe", "wsf", "wsh",
+];
+
+/// 添付ファイルをアップロード前に検証する。問題があれば(カテゴリ, 理由)を返す
+/// カテゴリは"zero_byte" / "missing_data" / "unreadable" / "unsupported_extension"のいずれか
+fn validate_file_preflight(
+  file_data: &crate::csv::processor::FileInfo,
+) -> Option<(&'static str, String)> {
+  let extension = file_data
+    .path_on_client
+    .rsplit('.')
+    .next()
+    .map(|ext| ext.to_lowercase());
+  if let Some(ext) = &extension {
+    if BLOCKED_FILE_EXTENSIONS.contains(&ext.as_str()) {
+      return Some((
+        "unsupported_extension",
+        format!("サポートされていない拡張子です: .{}", ext),
+      ));
+    }
+  }
+
+  match &file_data.version_data {
+    None => Some(("missing_data", "VersionDataが欠落しています".to_string())),
+    Some(crate::csv::processor::VersionData::Inline(data)) => {
+      if data.is_empty() {
+        return Some(("missing_data", "VersionDataが空です".to_string()));
+      }
+      match base64::Engine::decode(&base64::engine::general_purpose::STANDARD, data) {
+        Ok(bytes) if bytes.is_empty() => {
+          Some(("zero_byte", "ファイルサイズが0バイトです".to_string()))
+        }
+        Ok(_) => None,
+        Err(e) => Some(("unreadable", format!("base64デコードに失敗しました: {}", e))),
+      }
+    }
+    Some(crate::csv::processor::VersionData::OnDisk(path)) => match std::fs::metadata(path) {
+      Ok(meta) if meta.len() == 0 => {
+        Some(("zero_byte", "ファイルサイズが0バイトです".to_string()))
+      }
+      Ok(_) => None,
+      Err(e) => Some(("unreadable", format!("ファイルの読み込みに失敗しました: {}", e))),
+    },
+  }
+}
+
+/// ファイルマッピング処理の主要ステージ（進捗表示のcurrent_stage/total_stagesの算出に使用）
+const FILE_MAPPING_STAGES: &[&str] = &[
+  "validation",
+  "hubspot_init",
+  "extract_records",
+  "file_info",
+  "hubspot_search",
+  "file_processing",
+  "complete",
+];
+
+/// 結果CSV・JUnitレポート・サマリーJSONを1つのzipにまとめ、成功した場合はそのパスを返す。
+/// バンドル生成に失敗しても処理全体は継続させたいため、エラーはログに残すのみで空文字を返す
+async fn build_result_bundle(
+  temp_dir: &std::path::Path,
+  file_prefix: &str,
+  result_csv_path: &std::path::Path,
+  junit_report_path: Option<&std::path::Path>,
+  summaries: &[ObjectSummary],
+) -> String {
+  let bundle_path = temp_dir.join(format!("{}_{}.zip", file_prefix, chrono::Utc::now().timestamp()));
+
+  let summaries_json = match serde_json::to_string_pretty(summaries) {
+    Ok(json) => json,
+    Err(e) => {
+      log::warn!("サマリーJSONシリアライズ失敗: {}", e);
+      return String::new();
+    }
+  };
+
+  let input = crate::archive_bundle::BundleInput {
+    result_csv_path,
+    junit_report_path,
+    summaries_json: &summaries_json,
+    // アップロード済み添付ファイルの実体はSalesforceエクスポート側のCSV/外部ストレージに由来し、
+    // 実行のたびに肥大化する生バイト列を毎回バンドルへ複製するのは現実的でないため対象外とする
+    attachment_payloads: &[],
+  };
+
+  match crate::archive_bundle::write_bundle(&bundle_path, input).await {
+    Ok(()) => bundle_path.to_string_lossy().to_string(),
+    Err(e) => {
+      log::warn!("結果バンドル(zip)の作成に失敗: {}", e);
+      String::new()
+    }
+  }
+}
+
+/// ファイルマッピング処理の本体（新規実行・再開実行の両方から呼ばれる）
+#[allow(clippy::too_many_arguments)]
+async fn run_file_mapping(
+  run_id: String,
+  content_version_path: String,
+  content_document_link_path: String,
+  content_version_folder_path: String,
+  object_mappings: HashMap<String, ObjectMapping>,
+  concurrency: Option<usize>,
+  dry_run: bool,
+  file_backend: Option<crate::file_backend::FileBackendConfig>,
   window: tauri::Window,
 ) -> Result<FileMappingResponse, String> {
-  log::info!("ファイルマッピング処理開始");
+  log::info!(
+    "ファイルマッピング処理開始: run_id={}, dry_run={}",
+    run_id,
+    dry_run
+  );
+
+  let start_time = std::time::Instant::now();
 
-  // 進捗通知用のヘルパー関数
-  let emit_progress = |step: &str, progress: u8, message: &str| {
+  // 進捗通知用のヘルパー関数。items_processed/items_totalは件数ベースの進捗が無いステージでは0を渡す
+  let emit_progress = |step: &str, progress: u8, message: &str, items_processed: usize, items_total: usize| {
     let progress_info = ProgressInfo {
       step: step.to_string(),
       progress,
       message: message.to_string(),
+      current_stage: stage_index(FILE_MAPPING_STAGES, step),
+      total_stages: FILE_MAPPING_STAGES.len() as u8,
+      items_processed,
+      items_total,
+      estimated_seconds_remaining: estimate_seconds_remaining(start_time, items_processed, items_total),
     };
     let _ = window.emit("file-mapping-progress", &progress_info);
   };
 
-  emit_progress("validation", 5, "入力データを検証中...");
+  // キャンセル要求は実行全体で単一のフラグを共有するため、新しい実行の開始時にリセットする
+  let cancellation_state = window.app_handle().state::<CancellationState>();
+  cancellation_state
+    .cancelled
+    .store(false, std::sync::atomic::Ordering::SeqCst);
+  let cancelled_flag = cancellation_state.cancelled.clone();
+
+  let mut journal = RunJournal::open(&window.app_handle(), &run_id).map_err(|e| e.to_string())?;
+  if !dry_run && journal.completed_count() > 0 {
+    log::info!(
+      "ジャーナルから再開: {}件のレコードが処理済み",
+      journal.completed_count()
+    );
+    emit_progress(
+      "cache_restore",
+      3,
+      &format!(
+        "キャッシュから{}件のレコードを復元しました（再処理をスキップ）",
+        journal.completed_count()
+      ),
+      journal.completed_count(),
+      0,
+    );
+  }
+
+  emit_progress("validation", 5, "入力データを検証中...", 0, 0);
 
   // 1. CSVファイルの存在確認
   if let Err(e) =
@@ -92,7 +534,7 @@ pub async fn process_file_mapping(
     return Err(e.to_string());
   }
 
-  emit_progress("hubspot_init", 10, "HubSpot接続を初期化中...");
+  emit_progress("hubspot_init", 10, "HubSpot接続を初期化中...", 0, 0);
 
   // 2. 保存されたトークンを取得してHubSpotサービス初期化（期限切れの場合は自動リフレッシュ）
   let credentials = SecureStorage::get_credentials_with_refresh()
@@ -103,9 +545,9 @@ pub async fn process_file_mapping(
   let ui_domain = credentials
     .ui_domain
     .unwrap_or_else(|| "app.hubspot.com".to_string());
-  let hubspot_service = HubSpotService::new(credentials.token);
+  let hubspot_service = HubSpotService::new(credentials.token.expose_secret().to_string());
 
-  emit_progress("extract_records", 20, "対象レコードを抽出中...");
+  emit_progress("extract_records", 20, "対象レコードを抽出中...", 0, 0);
 
   // 3. マッピング対象レコードを抽出
   let target_records =
@@ -117,7 +559,7 @@ pub async fn process_file_mapping(
   let total_records: usize = target_records.values().map(|v| v.len()).sum();
   log::info!("マッピング対象レコード: {}件", total_records);
 
-  emit_progress("file_info", 35, "ファイル情報を取得中...");
+  emit_progress("file_info", 35, "ファイル情報を取得中...", 0, total_records);
 
   // 4. ファイル情報を取得してレコードをフィルタリング
   let content_folder = if content_version_folder_path.is_empty() {
@@ -126,7 +568,7 @@ pub async fn process_file_mapping(
     Some(content_version_folder_path.as_str())
   };
 
-  let (file_info, filtered_target_records) = match CsvProcessor::get_file_info_and_filter_records(
+  let (mut file_info, filtered_target_records) = match CsvProcessor::get_file_info_and_filter_records(
     &content_version_path,
     &target_records,
     content_folder,
@@ -137,7 +579,56 @@ pub async fn process_file_mapping(
 
   log::info!("ファイル情報: {}件", file_info.len());
 
-  emit_progress("hubspot_search", 50, "HubSpotレコードを検索中...");
+  // 4.5 アップロード前に壊れた/空の/未対応拡張子の添付ファイルを検出し、以降のパイプラインから除外する
+  // （実行時にアップロード失敗が連続するのではなく、具体的な理由を事前にユーザーへ示すため）
+  emit_progress("validation", 45, "添付ファイルを事前検証中...", 0, file_info.len());
+
+  let mut preflight_report = PreflightReport::default();
+  // (salesforce_id, hubspot_object, reason) — CSVライター作成後にskipped行として書き込む
+  let mut preflight_skip_rows: Vec<(String, String, String)> = Vec::new();
+
+  {
+    let mut bad_content_ids: HashSet<String> = HashSet::new();
+    for (content_document_id, data) in &file_info {
+      if let Some((category, reason)) = validate_file_preflight(data) {
+        bad_content_ids.insert(content_document_id.clone());
+        match category {
+          "zero_byte" => preflight_report.zero_byte_files += 1,
+          "missing_data" => preflight_report.missing_data_files += 1,
+          "unreadable" => preflight_report.unreadable_files += 1,
+          _ => preflight_report.unsupported_extension_files += 1,
+        }
+
+        for (prefix, records) in &filtered_target_records {
+          let hubspot_object = object_mappings
+            .get(prefix)
+            .map(|m| m.hubspot_object.clone())
+            .unwrap_or_default();
+          for (salesforce_id, doc_id) in records {
+            if doc_id == content_document_id {
+              preflight_skip_rows.push((salesforce_id.clone(), hubspot_object.clone(), reason.clone()));
+            }
+          }
+        }
+      }
+    }
+
+    if preflight_report.total() > 0 {
+      log::warn!(
+        "プリフライト検証で問題のある添付ファイルを検出: {}件 (0バイト:{}, データ欠落:{}, 読込/デコード失敗:{}, 未対応拡張子:{})",
+        bad_content_ids.len(),
+        preflight_report.zero_byte_files,
+        preflight_report.missing_data_files,
+        preflight_report.unreadable_files,
+        preflight_report.unsupported_extension_files,
+      );
+    }
+
+    // 問題のあるファイルは以降のアップロード処理対象から除外する
+    file_info.retain(|content_document_id, _| !bad_content_ids.contains(content_document_id));
+  }
+
+  emit_progress("hubspot_search", 50, "HubSpotレコードを検索中...", 0, total_records);
 
   // 5. 結果CSVファイルを一時ディレクトリに作成
   let temp_dir = std::env::temp_dir();
@@ -157,6 +648,10 @@ pub async fn process_file_mapping(
       "HubSpot Record URL",
       "Files Count",
       "Files Uploaded",
+      "Deduplicated Files",
+      "Content Hashes",
+      "Bytes Expected",
+      "Bytes Stored",
       "Note Created",
       "Status",
       "Reason",
@@ -167,6 +662,41 @@ pub async fn process_file_mapping(
   let mut all_processable_records = HashMap::new();
   let mut hubspot_record_cache = HashMap::new();
   let mut summaries: HashMap<String, ObjectSummary> = HashMap::new();
+  // レコード単位の処理結果レジャー（監査用途でexport_last_reportから書き出せる）
+  let mut ledger: Vec<RecordResult> = Vec::new();
+
+  // プリフライト検証でスキップしたファイルをネットワーク呼び出し前にCSV/レジャーへ記録する
+  for (salesforce_id, hubspot_object, reason) in &preflight_skip_rows {
+    let _ = csv_writer.write_record([
+      salesforce_id,
+      hubspot_object,
+      "",
+      "",
+      "1",
+      "0",
+      "0",
+      "",
+      "0",
+      "0",
+      "false",
+      "skipped",
+      reason,
+    ]);
+    ledger.push(RecordResult {
+      salesforce_id: salesforce_id.clone(),
+      hubspot_object: hubspot_object.clone(),
+      hubspot_record_id: String::new(),
+      record_url: String::new(),
+      files_uploaded: 0,
+      files_skipped: 1,
+      deduplicated_files: 0,
+      content_hashes: String::new(),
+      bytes_expected: 0,
+      bytes_stored: 0,
+      note_created: false,
+      error: Some(reason.clone()),
+    });
+  }
 
   for (prefix, records) in &filtered_target_records {
     if let Some(mapping) = object_mappings.get(prefix) {
@@ -225,10 +755,28 @@ pub async fn process_file_mapping(
                 "",
                 "0",
                 "0",
+                "0",
+                "",
+                "0",
+                "0",
                 "false",
                 "skipped",
                 "HubSpotにレコードが存在しません",
               ]);
+              ledger.push(RecordResult {
+                salesforce_id: missing_id.clone(),
+                hubspot_object: mapping.hubspot_object.clone(),
+                hubspot_record_id: String::new(),
+                record_url: String::new(),
+                files_uploaded: 0,
+                files_skipped: 0,
+                deduplicated_files: 0,
+                content_hashes: String::new(),
+                bytes_expected: 0,
+                bytes_stored: 0,
+                note_created: false,
+                error: Some("HubSpotにレコードが存在しません".to_string()),
+              });
             }
 
             // サマリー更新
@@ -241,6 +789,7 @@ pub async fn process_file_mapping(
                 skipped_count: 0,
                 error_count: 0,
                 uploaded_files: 0,
+                deduplicated_files: 0,
               })
               .skipped_count += missing_count;
           }
@@ -272,14 +821,38 @@ pub async fn process_file_mapping(
   let total_processable: usize = all_processable_records.values().map(|v| v.len()).sum();
   log::info!("処理可能レコード: {}件", total_processable);
 
-  emit_progress("file_processing", 70, "ファイル処理とアップロード中...");
+  emit_progress(
+    "file_processing",
+    70,
+    "ファイル処理とアップロード中...",
+    0,
+    total_processable,
+  );
 
   // 7. ファイル処理とノート作成
+  // HubSpotのレート制限に配慮しつつ、bufer_unordered(limit)で有限の並行数のアップロードパイプラインを回す
+
+  /// 並行実行する1レコード分のアップロードジョブ
+  struct UploadJob {
+    prefix: String,
+    hubspot_object: String,
+    record: crate::csv::processor::ProcessableRecord,
+    hubspot_record_id: String,
+    record_url: String,
+  }
+
+  /// パイプラインの結果
+  /// resultのタプルは(アップロード数, 重複排除数, ノート作成可否, コンテンツハッシュ一覧,
+  /// キャンセルにより打ち切ったか, 送信元バイト合計, HubSpot報告バイト合計, 整合性エラー一覧)
+  struct UploadOutcome {
+    job: UploadJob,
+    result: Result<(usize, usize, bool, Vec<String>, bool, u64, u64, Vec<String>)>,
+  }
+
+  let mut jobs = Vec::new();
 
   for (prefix, records) in &all_processable_records {
     if let Some(mapping) = object_mappings.get(prefix) {
-      log::info!("{}: {}件の処理可能レコードを処理", prefix, records.len());
-
       // オブジェクトサマリーを初期化（まだ存在しない場合のみ）
       summaries
         .entry(prefix.clone())
@@ -290,29 +863,14 @@ pub async fn process_file_mapping(
           skipped_count: 0,
           error_count: 0,
           uploaded_files: 0,
+          deduplicated_files: 0,
         });
 
-      for (i, record) in records.iter().enumerate() {
-        // 進捗更新
-        let progress = 70 + (20 * (i + 1) / records.len()) as u8;
-        emit_progress(
-          "file_processing",
-          progress,
-          &format!(
-            "処理中 ({}/{}): {}",
-            i + 1,
-            records.len(),
-            record.salesforce_id
-          ),
-        );
-
+      for record in records {
         let hubspot_record_id = hubspot_record_cache
           .get(&record.salesforce_id)
           .cloned()
           .unwrap_or_default();
-        let files_count = record.content_document_ids.len();
-
-        // HubSpotレコードURLを構築
         let record_url = if !hubspot_record_id.is_empty() {
           build_record_url(
             &ui_domain,
@@ -324,64 +882,374 @@ pub async fn process_file_mapping(
           String::new()
         };
 
-        match process_single_record(
-          &hubspot_service,
-          record,
-          &file_info,
-          &content_version_folder_path,
-          &mapping.hubspot_object,
-          &hubspot_record_cache,
-        )
-        .await
-        {
-          Ok((files_uploaded, note_created)) => {
-            // サマリー更新
-            if let Some(summary) = summaries.get_mut(prefix) {
-              summary.success_count += 1;
-              summary.uploaded_files += files_uploaded;
-            }
-
-            // CSVに結果書き込み
-            let _ = csv_writer.write_record([
-              &record.salesforce_id,
-              &mapping.hubspot_object,
-              &hubspot_record_id,
-              &record_url,
-              &files_count.to_string(),
-              &files_uploaded.to_string(),
-              &note_created.to_string(),
-              "success",
-              "",
-            ]);
-
-            log::info!(
-              "処理完了: {} - {}件のファイル",
-              record.salesforce_id,
-              files_count
-            );
+        // 前回の実行で処理済みのレコードはパイプラインに乗せず、ジャーナルの結果をそのまま反映する
+        // （ドライランでは実際に処理した記録ではないため対象外）
+        if !dry_run && journal.is_complete(&record.salesforce_id) {
+          if let Some(summary) = summaries.get_mut(prefix) {
+            summary.success_count += 1;
           }
-          Err(e) => {
-            // サマリー更新
-            if let Some(summary) = summaries.get_mut(prefix) {
-              summary.error_count += 1;
-            }
+          let _ = csv_writer.write_record([
+            &record.salesforce_id,
+            &mapping.hubspot_object,
+            &hubspot_record_id,
+            &record_url,
+            &record.content_document_ids.len().to_string(),
+            "0",
+            "0",
+            "",
+            "0",
+            "0",
+            "true",
+            "success",
+            "前回の実行で処理済み（再開によりスキップ）",
+          ]);
+          ledger.push(RecordResult {
+            salesforce_id: record.salesforce_id.clone(),
+            hubspot_object: mapping.hubspot_object.clone(),
+            hubspot_record_id: hubspot_record_id.clone(),
+            record_url: record_url.clone(),
+            files_uploaded: 0,
+            files_skipped: record.content_document_ids.len(),
+            deduplicated_files: 0,
+            content_hashes: String::new(),
+            bytes_expected: 0,
+            bytes_stored: 0,
+            note_created: true,
+            error: None,
+          });
+          continue;
+        }
 
-            // CSVにエラー書き込み
-            let _ = csv_writer.write_record([
-              &record.salesforce_id,
-              &mapping.hubspot_object,
-              &hubspot_record_id,
-              &record_url,
-              &files_count.to_string(),
-              "0",
-              "false",
-              "error",
-              &e.to_string(),
-            ]);
-
-            log::error!("レコード処理エラー {}: {}", record.salesforce_id, e);
-          }
+        jobs.push(UploadJob {
+          prefix: prefix.clone(),
+          hubspot_object: mapping.hubspot_object.clone(),
+          record: record.clone(),
+          hubspot_record_id,
+          record_url,
+        });
+      }
+    }
+  }
+
+  let total_jobs = jobs.len();
+
+  // ドライランでは実際のアップロード/ノート作成（upload_file_from_base64, create_note_for_record）を呼ばず、
+  // 件数の見積もりだけをCSVとサマリーに反映して終了する
+  if dry_run {
+    log::info!("ドライラン: {}件のレコードを検証（アップロードは行いません）", total_jobs);
+
+    for job in jobs {
+      let files_count = job.record.content_document_ids.len();
+      if let Some(summary) = summaries.get_mut(&job.prefix) {
+        summary.success_count += 1;
+        summary.uploaded_files += files_count;
+      }
+
+      let _ = csv_writer.write_record([
+        &job.record.salesforce_id,
+        &job.hubspot_object,
+        &job.hubspot_record_id,
+        &job.record_url,
+        &files_count.to_string(),
+        &files_count.to_string(),
+        "0",
+        "",
+        "0",
+        "0",
+        "true",
+        "dry_run",
+        "ドライランのため実際にはアップロード・ノート作成を行っていません",
+      ]);
+      ledger.push(RecordResult {
+        salesforce_id: job.record.salesforce_id.clone(),
+        hubspot_object: job.hubspot_object.clone(),
+        hubspot_record_id: job.hubspot_record_id.clone(),
+        record_url: job.record_url.clone(),
+        files_uploaded: files_count,
+        files_skipped: 0,
+        deduplicated_files: 0,
+        content_hashes: String::new(),
+        bytes_expected: 0,
+        bytes_stored: 0,
+        note_created: true,
+        error: None,
+      });
+    }
+
+    csv_writer
+      .flush()
+      .map_err(|e| format!("CSVフラッシュエラー: {}", e))?;
+
+    emit_progress("complete", 100, "ドライラン完了", total_jobs, total_jobs);
+
+    let junit_report_path = temp_dir.join(format!(
+      "hubspot_upload_result_{}.junit.xml",
+      chrono::Utc::now().timestamp()
+    ));
+    if let Err(e) = write_junit_report(&junit_report_path, &ledger) {
+      log::warn!("JUnitレポート書き込み失敗: {}", e);
+    }
+
+    let summaries_vec: Vec<ObjectSummary> = summaries.into_values().collect();
+    let bundle_path = build_result_bundle(
+      &temp_dir,
+      "hubspot_upload_bundle",
+      &result_csv_path,
+      Some(&junit_report_path),
+      &summaries_vec,
+    )
+    .await;
+
+    let response = FileMappingResponse {
+      result_csv_path: result_csv_path.to_string_lossy().to_string(),
+      summaries: summaries_vec,
+      records: ledger,
+      cancelled: false,
+      preflight: preflight_report,
+      junit_report_path: junit_report_path.to_string_lossy().to_string(),
+      bundle_path,
+    };
+    log::info!("ドライラン完了: {:?}", response);
+    return Ok(response);
+  }
+
+  let concurrency = concurrency.unwrap_or(DEFAULT_UPLOAD_CONCURRENCY).max(1);
+  log::info!(
+    "{}件のレコードをアップロードパイプラインへ投入（並行数: {}）",
+    total_jobs,
+    concurrency
+  );
+
+  let hubspot_service = Arc::new(hubspot_service);
+  let file_backend = crate::file_backend::build_file_backend(file_backend.as_ref(), hubspot_service.clone())
+    .await
+    .map_err(|e| e.to_string())?;
+  let file_info = Arc::new(file_info);
+  let hubspot_record_cache = Arc::new(hubspot_record_cache);
+  let completed_jobs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+  let window = Arc::new(window.clone());
+  let upload_index = Arc::new(Mutex::new(
+    UploadIndex::open(&window.app_handle()).map_err(|e| e.to_string())?,
+  ));
+
+  let mut outcomes = stream::iter(jobs.into_iter().map(|job| {
+    let hubspot_service = hubspot_service.clone();
+    let file_backend = file_backend.clone();
+    let file_info = file_info.clone();
+    let hubspot_record_cache = hubspot_record_cache.clone();
+    let upload_index = upload_index.clone();
+    let content_version_folder_path = content_version_folder_path.clone();
+    let completed_jobs = completed_jobs.clone();
+    let window = window.clone();
+    let cancelled_flag = cancelled_flag.clone();
+
+    async move {
+      // レコード処理の開始前にキャンセル要求を確認する（各レコード反復の先頭でのチェック）
+      if cancelled_flag.load(std::sync::atomic::Ordering::SeqCst) {
+        let done = completed_jobs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        let progress_info = ProgressInfo {
+          step: "file_processing".to_string(),
+          progress: 70 + (20 * done / total_jobs.max(1)) as u8,
+          message: format!("キャンセルによりスキップ ({}/{}): {}", done, total_jobs, job.record.salesforce_id),
+          current_stage: stage_index(FILE_MAPPING_STAGES, "file_processing"),
+          total_stages: FILE_MAPPING_STAGES.len() as u8,
+          items_processed: done,
+          items_total: total_jobs,
+          estimated_seconds_remaining: None,
+        };
+        let _ = window.emit("file-mapping-progress", &progress_info);
+        return UploadOutcome {
+          job,
+          result: Ok((0, 0, false, Vec::new(), true, 0, 0, Vec::new())),
+        };
+      }
+
+      let result = process_single_record(
+        &hubspot_service,
+        &file_backend,
+        &job.record,
+        &file_info,
+        &content_version_folder_path,
+        &job.hubspot_object,
+        &hubspot_record_cache,
+        &upload_index,
+        concurrency,
+        &cancelled_flag,
+      )
+      .await;
+
+      let done = completed_jobs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+      let progress = 70 + (20 * done / total_jobs.max(1)) as u8;
+      let progress_info = ProgressInfo {
+        step: "file_processing".to_string(),
+        progress,
+        message: format!("処理中 ({}/{}): {}", done, total_jobs, job.record.salesforce_id),
+        current_stage: stage_index(FILE_MAPPING_STAGES, "file_processing"),
+        total_stages: FILE_MAPPING_STAGES.len() as u8,
+        items_processed: done,
+        items_total: total_jobs,
+        estimated_seconds_remaining: estimate_seconds_remaining(start_time, done, total_jobs),
+      };
+      let _ = window.emit("file-mapping-progress", &progress_info);
+
+      UploadOutcome { job, result }
+    }
+  }))
+  .buffer_unordered(concurrency)
+  .collect::<Vec<_>>()
+  .await;
+
+  // 結果の書き込み順序を安定させるため、Salesforce IDの順に並べ直す
+  outcomes.sort_by_key(|o| o.job.record.salesforce_id.clone());
+
+  let mut migration_cancelled = false;
+
+  for outcome in outcomes {
+    let job = outcome.job;
+    let files_count = job.record.content_document_ids.len();
+
+    match outcome.result {
+      Ok((_, _, _, _, true, _, _, _)) => {
+        // キャンセル要求により処理をスキップしたレコード。次回実行時に再処理できるようジャーナルには記録しない
+        migration_cancelled = true;
+        if let Some(summary) = summaries.get_mut(&job.prefix) {
+          summary.skipped_count += 1;
         }
+
+        let _ = csv_writer.write_record([
+          &job.record.salesforce_id,
+          &job.hubspot_object,
+          &job.hubspot_record_id,
+          &job.record_url,
+          &files_count.to_string(),
+          "0",
+          "0",
+          "",
+          "0",
+          "0",
+          "false",
+          "cancelled",
+          "ユーザーによりキャンセルされました",
+        ]);
+
+        ledger.push(RecordResult {
+          salesforce_id: job.record.salesforce_id.clone(),
+          hubspot_object: job.hubspot_object.clone(),
+          hubspot_record_id: job.hubspot_record_id.clone(),
+          record_url: job.record_url.clone(),
+          files_uploaded: 0,
+          files_skipped: files_count,
+          deduplicated_files: 0,
+          content_hashes: String::new(),
+          bytes_expected: 0,
+          bytes_stored: 0,
+          note_created: false,
+          error: Some("cancelled".to_string()),
+        });
+      }
+      Ok((files_uploaded, deduplicated_files, note_created, content_hashes, _, bytes_expected, bytes_stored, integrity_errors)) => {
+        if let Some(summary) = summaries.get_mut(&job.prefix) {
+          summary.success_count += 1;
+          summary.uploaded_files += files_uploaded;
+          summary.deduplicated_files += deduplicated_files;
+        }
+
+        let content_hashes_joined = content_hashes.join(";");
+        let integrity_errors_joined = integrity_errors.join("; ");
+        // 添付ファイルの一部でサイズ不一致が検出された場合は"partial"としてReasonに理由を残す
+        let status = if integrity_errors_joined.is_empty() {
+          "success"
+        } else {
+          "partial"
+        };
+        let _ = csv_writer.write_record([
+          &job.record.salesforce_id,
+          &job.hubspot_object,
+          &job.hubspot_record_id,
+          &job.record_url,
+          &files_count.to_string(),
+          &files_uploaded.to_string(),
+          &deduplicated_files.to_string(),
+          &content_hashes_joined,
+          &bytes_expected.to_string(),
+          &bytes_stored.to_string(),
+          &note_created.to_string(),
+          status,
+          &integrity_errors_joined,
+        ]);
+
+        // ジャーナルに完了を記録（ここで永続化されるまでは再開時に再処理される）
+        if let Err(e) = journal.record_event(RunEvent {
+          salesforce_id: job.record.salesforce_id.clone(),
+          hubspot_record_id: Some(job.hubspot_record_id.clone()),
+          uploaded_file_ids: Vec::new(),
+          note_created,
+        }) {
+          log::warn!("ジャーナル書き込み失敗 {}: {}", job.record.salesforce_id, e);
+        }
+
+        log::info!(
+          "処理完了: {} - {}件のファイル",
+          job.record.salesforce_id,
+          files_count
+        );
+
+        ledger.push(RecordResult {
+          salesforce_id: job.record.salesforce_id.clone(),
+          hubspot_object: job.hubspot_object.clone(),
+          hubspot_record_id: job.hubspot_record_id.clone(),
+          record_url: job.record_url.clone(),
+          files_uploaded,
+          files_skipped: files_count.saturating_sub(files_uploaded),
+          deduplicated_files,
+          content_hashes: content_hashes_joined,
+          bytes_expected,
+          bytes_stored,
+          note_created,
+          error: if integrity_errors_joined.is_empty() {
+            None
+          } else {
+            Some(integrity_errors_joined)
+          },
+        });
+      }
+      Err(e) => {
+        if let Some(summary) = summaries.get_mut(&job.prefix) {
+          summary.error_count += 1;
+        }
+
+        let _ = csv_writer.write_record([
+          &job.record.salesforce_id,
+          &job.hubspot_object,
+          &job.hubspot_record_id,
+          &job.record_url,
+          &files_count.to_string(),
+          "0",
+          "0",
+          "",
+          "0",
+          "0",
+          "false",
+          "error",
+          &e.to_string(),
+        ]);
+
+        log::error!("レコード処理エラー {}: {}", job.record.salesforce_id, e);
+
+        ledger.push(RecordResult {
+          salesforce_id: job.record.salesforce_id.clone(),
+          hubspot_object: job.hubspot_object.clone(),
+          hubspot_record_id: job.hubspot_record_id.clone(),
+          record_url: job.record_url.clone(),
+          files_uploaded: 0,
+          files_skipped: files_count,
+          deduplicated_files: 0,
+          content_hashes: String::new(),
+          bytes_expected: 0,
+          bytes_stored: 0,
+          note_created: false,
+          error: Some(e.to_string()),
+        });
       }
     }
   }
@@ -391,89 +1259,137 @@ pub async fn process_file_mapping(
     .flush()
     .map_err(|e| format!("CSVフラッシュエラー: {}", e))?;
 
-  emit_progress("complete", 100, "処理完了");
+  emit_progress("complete", 100, "処理完了", total_jobs, total_jobs);
+
+  let junit_report_path = temp_dir.join(format!(
+    "hubspot_upload_result_{}.junit.xml",
+    chrono::Utc::now().timestamp()
+  ));
+  if let Err(e) = write_junit_report(&junit_report_path, &ledger) {
+    log::warn!("JUnitレポート書き込み失敗: {}", e);
+  }
+
+  let summaries_vec: Vec<ObjectSummary> = summaries.into_values().collect();
+  let bundle_path = build_result_bundle(
+    &temp_dir,
+    "hubspot_upload_bundle",
+    &result_csv_path,
+    Some(&junit_report_path),
+    &summaries_vec,
+  )
+  .await;
 
   let response = FileMappingResponse {
     result_csv_path: result_csv_path.to_string_lossy().to_string(),
-    summaries: summaries.into_values().collect(),
+    summaries: summaries_vec,
+    records: ledger,
+    cancelled: migration_cancelled,
+    preflight: preflight_report,
+    junit_report_path: junit_report_path.to_string_lossy().to_string(),
+    bundle_path,
   };
 
+  if let Err(e) = journal.checkpoint() {
+    log::warn!("最終チェックポイント書き込み失敗: {}", e);
+  }
+  // 正常に最後まで完了した場合のみジャーナルを削除する
+  // （キャンセルされた場合や途中で失敗して早期リターンした場合はジャーナルを残し、再開できるようにする）
+  if migration_cancelled {
+    log::info!("キャンセルにより処理を中断しました。次回実行時に未処理分から再開します");
+  } else if let Err(e) = journal.cleanup() {
+    log::warn!("ジャーナル削除失敗: {}", e);
+  }
+
   log::info!("ファイルマッピング処理完了: {:?}", response);
   Ok(response)
 }
 
 /// 単一レコードの処理
 /// ファイルアップロードとノート作成を行う
+#[allow(clippy::too_many_arguments)]
 async fn process_single_record(
   hubspot_service: &HubSpotService,
+  file_backend: &Arc<dyn crate::file_backend::FileBackend>,
   record: &crate::csv::processor::ProcessableRecord,
   file_info: &HashMap<String, crate::csv::processor::FileInfo>,
   _content_folder_path: &str,
   hubspot_object: &str,
   hubspot_record_cache: &HashMap<String, String>,
-) -> Result<(usize, bool)> {
+  upload_index: &Arc<Mutex<UploadIndex>>,
+  concurrency: usize,
+  cancelled_flag: &Arc<std::sync::atomic::AtomicBool>,
+) -> Result<(usize, usize, bool, Vec<String>, bool, u64, u64, Vec<String>)> {
+  // レコード内の各ファイルを並行でアップロードする（レコード単位の並行数と同じ値を使うが、
+  // 上限はレコードごとに独立して適用され、他レコードのアップロードとは共有しない）
+  // upload_one_fileはファイルごとにキャンセル要求を確認し、確認後は未アップロードのままスキップする
+  let results: Vec<Result<Option<UploadedFile>>> = stream::iter(
+    record
+      .content_document_ids
+      .iter()
+      .filter_map(|content_doc_id| file_info.get(content_doc_id)),
+  )
+  .map(|file_data| upload_one_file(file_backend, file_data, upload_index, cancelled_flag))
+  .buffer_unordered(concurrency.max(1))
+  .collect()
+  .await;
+
   let mut uploaded_files = 0;
-  let mut file_ids = Vec::new();
-
-  // 各ファイルを処理
-  for content_doc_id in &record.content_document_ids {
-    if let Some(file_data) = file_info.get(content_doc_id) {
-      // ファイル名の拡張子を小文字に統一（HubSpot側の仕様に合わせる）
-      let filename = file_data.path_on_client.clone();
-      let safe_filename = if let Some(dot_pos) = filename.rfind('.') {
-        let (name, ext) = filename.split_at(dot_pos);
-        format!("{}_{}{}", name, file_data.version_id, ext.to_lowercase())
-      } else {
-        format!("{}_{}", filename, file_data.version_id)
-      };
+  let mut deduplicated_files = 0;
+  let mut file_refs = Vec::new();
+  let mut content_hashes = Vec::new();
+  let mut bytes_expected_total: u64 = 0;
+  let mut bytes_stored_total: u64 = 0;
+  let mut integrity_errors = Vec::new();
+  for result in results {
+    if let Some(uploaded) = result? {
+      if let Some(expected) = uploaded.bytes_expected {
+        bytes_expected_total += expected;
+      }
+      if let Some(stored) = uploaded.bytes_stored {
+        bytes_stored_total += stored;
+      }
 
-      // HubSpotでファイル存在確認
-      match hubspot_service
-        .get_file_by_path(&format!("salesforce/{}", safe_filename))
-        .await?
-      {
-        Some(existing_file) => {
-          // ファイルが既に存在する場合
-          log::debug!("ファイルが既に存在: {}", safe_filename);
-          file_ids.push(existing_file.id);
-        }
-        None => {
-          // ファイルが存在しない場合はbase64データからアップロード
-          if let Some(version_data) = &file_data.version_data {
-            match hubspot_service
-              .upload_file_from_base64(version_data, &safe_filename)
-              .await
-            {
-              Ok(file_id) => {
-                uploaded_files += 1;
-                file_ids.push(file_id);
-                log::debug!("アップロード成功: {}", safe_filename);
-              }
-              Err(e) => {
-                log::warn!("アップロード失敗 {}: {}", safe_filename, e);
-              }
-            }
-          } else {
-            log::warn!("バージョンデータがありません: {}", safe_filename);
-          }
-        }
+      if let Some(err) = uploaded.integrity_error {
+        // サイズ不一致・上限超過のファイルはノートに添付しない
+        integrity_errors.push(err);
+        continue;
+      }
+
+      if uploaded.was_uploaded {
+        uploaded_files += 1;
+      }
+      if uploaded.was_deduplicated {
+        deduplicated_files += 1;
+      }
+      if let Some(hash) = uploaded.content_hash {
+        content_hashes.push(hash);
       }
+      file_refs.push(uploaded.file_ref);
     }
   }
 
+  // キャンセル要求があった場合、ここまでにアップロード済みのファイルは結果に残しつつ、ノート作成は行わない
+  if cancelled_flag.load(std::sync::atomic::Ordering::SeqCst) {
+    return Ok((
+      uploaded_files,
+      deduplicated_files,
+      false,
+      content_hashes,
+      true,
+      bytes_expected_total,
+      bytes_stored_total,
+      integrity_errors,
+    ));
+  }
+
   // ノート作成
-  let note_created = if !file_ids.is_empty() {
+  let note_created = if !file_refs.is_empty() {
     let hubspot_record_id = hubspot_record_cache
       .get(&record.salesforce_id)
       .ok_or_else(|| anyhow::anyhow!("HubSpotレコードIDが見つかりません"))?;
 
     match hubspot_service
-      .create_note_for_record(
-        hubspot_record_id,
-        hubspot_object,
-        "添付ファイル",
-        Some(file_ids),
-      )
+      .create_note_for_record(hubspot_record_id, hubspot_object, "添付ファイル", &file_refs)
       .await
     {
       Ok(_) => true,
@@ -487,7 +1403,235 @@ async fn process_single_record(
     false
   };
 
-  Ok((uploaded_files, note_created))
+  Ok((
+    uploaded_files,
+    deduplicated_files,
+    note_created,
+    content_hashes,
+    false,
+    bytes_expected_total,
+    bytes_stored_total,
+    integrity_errors,
+  ))
+}
+
+/// `upload_one_file`が返す1ファイル分の処理結果
+struct UploadedFile {
+  /// アップロード先バックエンドでの参照（新規アップロードしたもの、または既存流用したもの）
+  file_ref: crate::file_backend::FileRef,
+  /// コンテンツのSHA-256ハッシュ（アップロード結果CSVの監査用カラムに使う）
+  content_hash: Option<String>,
+  /// 新規にバイト列をアップロードしたか
+  was_uploaded: bool,
+  /// コンテンツハッシュがインデックスに一致し、アップロードを省略したか
+  was_deduplicated: bool,
+  /// 送信元バイト列の長さ（新規アップロード時のみ。流用/重複排除時は検証対象が無いためNone）
+  bytes_expected: Option<u64>,
+  /// アップロード後にHubSpotへ再問い合わせして得た報告サイズ
+  bytes_stored: Option<u64>,
+  /// サイズ上限超過またはサイズ不一致が検出された場合の理由（このファイルはノートに添付しない）
+  integrity_error: Option<String>,
+}
+
+/// 1ファイル分のアップロード処理。既存ファイルの検出、コンテンツハッシュによる重複排除、新規アップロードを行う
+/// （アップロード対象が見つからなかった場合は`None`を返す）
+async fn upload_one_file(
+  file_backend: &Arc<dyn crate::file_backend::FileBackend>,
+  file_data: &crate::csv::processor::FileInfo,
+  upload_index: &Arc<Mutex<UploadIndex>>,
+  cancelled_flag: &Arc<std::sync::atomic::AtomicBool>,
+) -> Result<Option<UploadedFile>> {
+  use base64::Engine;
+  use crate::file_backend::FileRef;
+
+  // ファイルアップロードの合間にキャンセル要求を確認し、要求後は以降のファイルをアップロードしない
+  if cancelled_flag.load(std::sync::atomic::Ordering::SeqCst) {
+    return Ok(None);
+  }
+
+  // ファイル名の拡張子を小文字に統一（HubSpot側の仕様に合わせる）
+  let filename = file_data.path_on_client.clone();
+  let safe_filename = if let Some(dot_pos) = filename.rfind('.') {
+    let (name, ext) = filename.split_at(dot_pos);
+    format!("{}_{}{}", name, file_data.version_id, ext.to_lowercase())
+  } else {
+    format!("{}_{}", filename, file_data.version_id)
+  };
+
+  // 同一バイト列のファイルを重複アップロードしないよう、まずコンテンツハッシュでインデックスを確認する
+  // （異なるContentVersion IDでエクスポートされた同一内容のファイルを検出するため、パスだけでは不十分）
+  let content_hash = file_data
+    .version_data
+    .as_ref()
+    .and_then(|data| data.read_bytes().ok())
+    .map(|bytes| compute_content_hash(&bytes));
+
+  // コンテンツハッシュによる重複排除はインデックスがHubSpotファイルIDのみを記録するため、
+  // 対応するバックエンド（現状HubSpotのみ）でのみ確認する
+  let indexed_file_id = if file_backend.supports_content_dedup() {
+    content_hash
+      .as_ref()
+      .and_then(|hash| upload_index.lock().unwrap().lookup(hash))
+  } else {
+    None
+  };
+
+  if let Some(existing_id) = indexed_file_id {
+    log::debug!("コンテンツハッシュが一致、アップロードをスキップ: {}", safe_filename);
+    return Ok(Some(UploadedFile {
+      file_ref: FileRef::HubSpotFile(existing_id),
+      content_hash: content_hash.clone(),
+      was_uploaded: false,
+      was_deduplicated: true,
+      bytes_expected: None,
+      bytes_stored: None,
+      integrity_error: None,
+    }));
+  }
+
+  // インデックスに無い場合は、従来のパスベースの存在確認にフォールバックする
+  // （対応しないバックエンドはデフォルトで`None`を返すため、常に新規アップロードに進む）
+  match file_backend
+    .find_by_path(&format!("salesforce/{}", safe_filename))
+    .await?
+  {
+    Some(existing_ref) => {
+      // ファイルが既に存在する場合
+      log::debug!("ファイルが既に存在: {}", safe_filename);
+      if let (Some(hash), FileRef::HubSpotFile(existing_id)) = (&content_hash, &existing_ref) {
+        if let Err(e) = upload_index
+          .lock()
+          .unwrap()
+          .record(hash.clone(), existing_id.clone())
+        {
+          log::warn!("アップロードインデックス書き込み失敗: {}", e);
+        }
+      }
+      Ok(Some(UploadedFile {
+        file_ref: existing_ref,
+        content_hash: content_hash.clone(),
+        was_uploaded: false,
+        was_deduplicated: false,
+        bytes_expected: None,
+        bytes_stored: None,
+        integrity_error: None,
+      }))
+    }
+    None => {
+      // ファイルが存在しない場合はbase64データからアップロード
+      if let Some(version_data) = &file_data.version_data {
+        // HubSpot Files APIにはファイル単位の任意メタデータ欄が無いため、
+        // インデックスを後から再構築できるようファイル名にハッシュを埋め込んでおく
+        let upload_filename = match &content_hash {
+          Some(hash) => format!("{}__{}", &hash[..16], safe_filename),
+          None => safe_filename.clone(),
+        };
+
+        // OnDiskの場合はハッシュ計算時に続いてここで再度ファイルを読み込む（読み込みは1ファイルずつ行う）
+        let base64_data = match version_data.to_base64() {
+          Ok(data) => data,
+          Err(e) => {
+            log::warn!("バージョンデータ読み込み失敗 {}: {}", safe_filename, e);
+            return Ok(None);
+          }
+        };
+
+        // 送信元バイト列の長さを事前に確認し、バックエンドのサイズ上限を超える場合は
+        // アップロードを試みずエラーとして扱う
+        let decoded_bytes = match base64::engine::general_purpose::STANDARD.decode(&base64_data) {
+          Ok(bytes) => bytes,
+          Err(e) => {
+            log::warn!("base64デコードエラー {}: {}", safe_filename, e);
+            return Ok(None);
+          }
+        };
+        let expected_bytes = decoded_bytes.len() as u64;
+
+        if let Some(limit) = file_backend.max_upload_bytes() {
+          if expected_bytes > limit {
+            log::warn!(
+              "ファイルサイズ上限超過のためアップロードをスキップ: {} ({}バイト)",
+              safe_filename,
+              expected_bytes
+            );
+            return Ok(Some(UploadedFile {
+              // 添付対象外のため参照先は実際には読まれない（integrity_errorが設定され呼び出し元がskipする）
+              file_ref: FileRef::HubSpotFile(String::new()),
+              content_hash: content_hash.clone(),
+              was_uploaded: false,
+              was_deduplicated: false,
+              bytes_expected: Some(expected_bytes),
+              bytes_stored: None,
+              integrity_error: Some(format!(
+                "file size {} bytes exceeds limit of {} bytes",
+                expected_bytes, limit
+              )),
+            }));
+          }
+        }
+
+        match file_backend.upload(decoded_bytes, &upload_filename).await {
+          Ok(file_ref) => {
+            // アップロード直後にバックエンド側の報告サイズを再取得し、送信元バイト列と一致するか検証する
+            // （対応しないバックエンドはデフォルトで`None`を返すため、検証自体をスキップする）
+            let bytes_stored = match file_backend.verify_uploaded_size(&file_ref).await {
+              Ok(size) => size,
+              Err(e) => {
+                log::warn!("アップロード後のメタデータ取得に失敗 {}: {}", safe_filename, e);
+                None
+              }
+            };
+
+            let integrity_error = match bytes_stored {
+              Some(stored) if stored != expected_bytes => Some(format!(
+                "integrity mismatch: expected {} bytes got {}",
+                expected_bytes, stored
+              )),
+              _ => None,
+            };
+
+            if integrity_error.is_some() {
+              log::warn!(
+                "整合性検証に失敗 {}: {}",
+                safe_filename,
+                integrity_error.as_deref().unwrap_or_default()
+              );
+            } else {
+              if let (Some(hash), FileRef::HubSpotFile(file_id)) = (&content_hash, &file_ref) {
+                if file_backend.supports_content_dedup() {
+                  if let Err(e) = upload_index
+                    .lock()
+                    .unwrap()
+                    .record(hash.clone(), file_id.clone())
+                  {
+                    log::warn!("アップロードインデックス書き込み失敗: {}", e);
+                  }
+                }
+              }
+              log::debug!("アップロード成功: {}", safe_filename);
+            }
+
+            Ok(Some(UploadedFile {
+              file_ref,
+              content_hash: content_hash.clone(),
+              was_uploaded: true,
+              was_deduplicated: false,
+              bytes_expected: Some(expected_bytes),
+              bytes_stored,
+              integrity_error,
+            }))
+          }
+          Err(e) => {
+            log::warn!("アップロード失敗 {}: {}", safe_filename, e);
+            Ok(None)
+          }
+        }
+      } else {
+        log::warn!("バージョンデータがありません: {}", safe_filename);
+        Ok(None)
+      }
+    }
+  }
 }
 
 /// CSVファイルを分析してオブジェクトグループを取得
@@ -525,7 +1669,7 @@ pub async fn get_hubspot_objects() -> Result<Vec<HubSpotObject>, String> {
     .await
     .map_err(|_| "認証情報が見つかりません。再ログインしてください。")?;
 
-  let service = HubSpotService::new(credentials.token);
+  let service = HubSpotService::new(credentials.token.expose_secret().to_string());
 
   match service.get_all_objects().await {
     Ok(objects) => {
@@ -536,6 +1680,97 @@ pub async fn get_hubspot_objects() -> Result<Vec<HubSpotObject>, String> {
   }
 }
 
+/// 実ポータルへサンプルファイルをアップロードしてスループットを計測する
+/// ユーザーが本番のCSVで全件インポートする前に、所要時間の目安を把握できるようにする
+#[command]
+pub async fn benchmark_upload(sample_count: usize) -> Result<BenchmarkReport, String> {
+  log::info!("アップロードベンチマーク開始: サンプル数={}", sample_count);
+
+  let credentials = SecureStorage::get_credentials_with_refresh()
+    .await
+    .map_err(|_| "認証情報が見つかりません。再ログインしてください。")?;
+  let hubspot_service = HubSpotService::new(credentials.token.expose_secret().to_string());
+
+  // 合成サンプルデータ（1ファイルあたり約10KB）
+  const SAMPLE_FILE_SIZE: usize = 10 * 1024;
+  let sample_bytes = vec![0x42u8; SAMPLE_FILE_SIZE];
+  let sample_base64 = {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(&sample_bytes)
+  };
+
+  let mut latencies_ms = Vec::with_capacity(sample_count);
+  let total_start = std::time::Instant::now();
+
+  for i in 0..sample_count {
+    // upload_file_from_base64は常に"salesforce"フォルダへアップロードするため、
+    // ファイル名にプレフィックスを付けてベンチマーク用サンプルだと識別できるようにする
+    let filename = format!("start-connect-benchmark_{}.bin", i);
+    let start = std::time::Instant::now();
+    hubspot_service
+      .upload_file_from_base64(&sample_base64, &filename)
+      .await
+      .map_err(|e| format!("ベンチマーク用アップロードに失敗しました: {}", e))?;
+    latencies_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+  }
+
+  let elapsed_ms = total_start.elapsed().as_millis();
+  let total_bytes = (SAMPLE_FILE_SIZE * sample_count) as u64;
+
+  latencies_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  let percentile = |p: f64| -> f64 {
+    if latencies_ms.is_empty() {
+      return 0.0;
+    }
+    let idx = ((latencies_ms.len() as f64 - 1.0) * p).round() as usize;
+    latencies_ms[idx]
+  };
+
+  let elapsed_secs = (elapsed_ms as f64 / 1000.0).max(f64::EPSILON);
+  let report = BenchmarkReport {
+    sample_count,
+    total_bytes,
+    elapsed_ms,
+    files_per_sec: sample_count as f64 / elapsed_secs,
+    bytes_per_sec: total_bytes as f64 / elapsed_secs,
+    p50_latency_ms: percentile(0.50),
+    p95_latency_ms: percentile(0.95),
+  };
+
+  log::info!(
+    "アップロードベンチマーク完了: {:.2} files/sec, {:.0} bytes/sec（ベンチマーク用ファイルは salesforce フォルダに start-connect-benchmark_* として残るため、手動で削除してください）",
+    report.files_per_sec,
+    report.bytes_per_sec
+  );
+  Ok(report)
+}
+
+/// Chatter添付ファイル（ContentDocumentId一覧）をContentVersion.csvから解決する
+/// eager=trueの場合はconcurrencyで指定した並行数でバイト列まで事前取得する
+#[command]
+pub async fn resolve_chatter_attachments(
+  content_version_path: String,
+  content_document_ids: Vec<String>,
+  content_version_folder_path: Option<String>,
+  eager: Option<bool>,
+  concurrency: Option<usize>,
+) -> Result<Vec<chatter_attachments::MaterializedAttachment>, String> {
+  let target_ids: HashSet<String> = content_document_ids.iter().cloned().collect();
+
+  let file_info = CsvProcessor::load_file_info_for_ids(
+    &content_version_path,
+    &target_ids,
+    content_version_folder_path.as_deref(),
+  )
+  .map_err(|e| format!("添付ファイル情報の読み込みエラー: {}", e))?;
+
+  if eager.unwrap_or(false) {
+    Ok(chatter_attachments::resolve_eager(&content_document_ids, &file_info, concurrency).await)
+  } else {
+    Ok(chatter_attachments::resolve_lazy(&content_document_ids, &file_info))
+  }
+}
+
 /// 結果CSVを指定パスに保存
 #[command]
 pub async fn save_result_csv(temp_path: String, save_path: String) -> Result<(), String> {
@@ -607,6 +1842,17 @@ pub async fn analyze_chatter_files(
   Ok(AnalyzeResponse { object_groups })
 }
 
+/// Chatter移行処理の主要ステージ（進捗表示のcurrent_stage/total_stagesの算出に使用）
+const CHATTER_MIGRATION_STAGES: &[&str] = &[
+  "validation",
+  "hubspot_init",
+  "extract_records",
+  "load_comments",
+  "hubspot_search",
+  "create_notes",
+  "complete",
+];
+
 /// Chatter移行処理のメインコマンド
 #[command]
 pub async fn process_chatter_migration(
@@ -616,21 +1862,47 @@ pub async fn process_chatter_migration(
   content_version_path: String,
   content_document_link_path: String,
   feed_attachment_path: String,
+  feed_like_path: String,
   object_mappings: HashMap<String, ObjectMapping>,
+  mqtt_broker_host: Option<String>,
+  mqtt_broker_port: Option<u16>,
+  mqtt_qos: Option<u8>,
+  concurrency: Option<usize>,
+  default_locale: Option<String>,
+  file_backend: Option<crate::file_backend::FileBackendConfig>,
+  processing_config: Option<crate::csv::processor::ChatterProcessingConfig>,
   window: tauri::Window,
 ) -> Result<FileMappingResponse, String> {
   log::info!("Chatter移行処理開始");
-
-  let emit_progress = |step: &str, progress: u8, message: &str| {
+  let processing_config = processing_config.unwrap_or_default();
+  let attachment_concurrency = concurrency.unwrap_or(DEFAULT_UPLOAD_CONCURRENCY).max(1);
+  // 生成するノートの表示言語。未指定/未対応のコードは英語にフォールバックする
+  let locale = Locale::from_code(default_locale.as_deref());
+  let start_time = std::time::Instant::now();
+
+  // キャンセル要求は実行全体で単一のフラグを共有するため、新しい実行の開始時にリセットする
+  let cancellation_state = window.app_handle().state::<CancellationState>();
+  cancellation_state
+    .cancelled
+    .store(false, std::sync::atomic::Ordering::SeqCst);
+  let cancelled_flag = cancellation_state.cancelled.clone();
+
+  // 進捗通知用のヘルパー関数。items_processed/items_totalは件数ベースの進捗が無いステージでは0を渡す
+  let emit_progress = |step: &str, progress: u8, message: &str, items_processed: usize, items_total: usize| {
     let progress_info = ProgressInfo {
       step: step.to_string(),
       progress,
       message: message.to_string(),
+      current_stage: stage_index(CHATTER_MIGRATION_STAGES, step),
+      total_stages: CHATTER_MIGRATION_STAGES.len() as u8,
+      items_processed,
+      items_total,
+      estimated_seconds_remaining: estimate_seconds_remaining(start_time, items_processed, items_total),
     };
     let _ = window.emit("chatter-migration-progress", &progress_info);
   };
 
-  emit_progress("validation", 5, "入力データを検証中...");
+  emit_progress("validation", 5, "入力データを検証中...", 0, 0);
 
   // CSVファイルの存在確認
   if !std::path::Path::new(&feed_item_path).exists() {
@@ -640,7 +1912,7 @@ pub async fn process_chatter_migration(
     return Err("FeedComment.csvが見つかりません".to_string());
   }
 
-  emit_progress("hubspot_init", 10, "HubSpot接続を初期化中...");
+  emit_progress("hubspot_init", 10, "HubSpot接続を初期化中...", 0, 0);
 
   let credentials = SecureStorage::get_credentials_with_refresh()
     .await
@@ -650,9 +1922,9 @@ pub async fn process_chatter_migration(
   let ui_domain = credentials
     .ui_domain
     .unwrap_or_else(|| "app.hubspot.com".to_string());
-  let hubspot_service = HubSpotService::new(credentials.token);
+  let hubspot_service = HubSpotService::new(credentials.token.expose_secret().to_string());
 
-  emit_progress("extract_records", 20, "Chatterレコードを抽出中...");
+  emit_progress("extract_records", 20, "Chatterレコードを抽出中...", 0, 0);
 
   // FeedItemを読み込み
   let feed_items_by_prefix =
@@ -665,7 +1937,15 @@ pub async fn process_chatter_migration(
     .flat_map(|items| items.iter().map(|item| item.id.clone()))
     .collect();
 
-  emit_progress("load_comments", 30, "コメントと添付ファイルを読み込み中...");
+  let total_feed_items = target_feed_item_ids.len();
+
+  emit_progress(
+    "load_comments",
+    30,
+    "コメントと添付ファイルを読み込み中...",
+    0,
+    total_feed_items,
+  );
 
   // FeedCommentを読み込み
   let comments_by_feed_item =
@@ -699,6 +1979,10 @@ pub async fn process_chatter_migration(
   )
   .map_err(|e| format!("FeedAttachment読み込みエラー: {}", e))?;
 
+  // FeedLikeからいいね/リアクションを読み込み
+  let feed_likes = CsvProcessor::load_feed_likes(&feed_like_path)
+    .map_err(|e| format!("FeedLike読み込みエラー: {}", e))?;
+
   // ContentVersionからファイル情報を取得
   let file_info = if !content_version_path.is_empty() {
     let mut all_content_document_ids = HashSet::new();
@@ -731,7 +2015,7 @@ pub async fn process_chatter_migration(
             crate::csv::processor::FileInfo {
               version_id: record.id,
               path_on_client: filename.to_string(),
-              version_data: record.version_data,
+              version_data: record.version_data.map(crate::csv::processor::VersionData::Inline),
             },
           );
         }
@@ -745,7 +2029,13 @@ pub async fn process_chatter_migration(
     HashMap::new()
   };
 
-  emit_progress("hubspot_search", 40, "HubSpotレコードを検索中...");
+  emit_progress(
+    "hubspot_search",
+    40,
+    "HubSpotレコードを検索中...",
+    0,
+    total_feed_items,
+  );
 
   // 結果CSVファイルを作成
   let temp_dir = std::env::temp_dir();
@@ -835,6 +2125,7 @@ pub async fn process_chatter_migration(
                 skipped_count: 0,
                 error_count: 0,
                 uploaded_files: 0,
+                deduplicated_files: 0,
               })
               .skipped_count += missing_count;
           }
@@ -849,7 +2140,7 @@ pub async fn process_chatter_migration(
     }
   }
 
-  emit_progress("create_notes", 60, "ノートを作成中...");
+  emit_progress("create_notes", 60, "ノートを作成中...", 0, 0);
 
   // 処理可能レコードをグループ化
   let processable_records = CsvProcessor::group_chatter_records(
@@ -858,17 +2149,125 @@ pub async fn process_chatter_migration(
     &hubspot_record_cache,
     content_document_links,
     feed_attachments,
+    &content_version_to_document,
+    &feed_likes,
+    &processing_config,
   );
 
-  // ノート作成処理
-  for (i, record) in processable_records.iter().enumerate() {
-    let progress = 60 + (30 * (i + 1) / processable_records.len()) as u8;
+  // 指定があれば、HubSpotへのノート作成に加えてMQTTブローカーへも処理済みレコードをパブリッシュする
+  if let Some(host) = &mqtt_broker_host {
     emit_progress(
-      "create_notes",
-      progress,
-      &format!("処理中 ({}/{})", i + 1, processable_records.len()),
+      "mqtt_publish",
+      55,
+      "MQTTブローカーへパブリッシュ中...",
+      0,
+      processable_records.len(),
     );
 
+    let qos = match mqtt_qos.unwrap_or(1) {
+      0 => mqtt_sink::PublishQos::AtMostOnce,
+      2 => mqtt_sink::PublishQos::ExactlyOnce,
+      _ => mqtt_sink::PublishQos::AtLeastOnce,
+    };
+
+    let sink = mqtt_sink::MqttSink::connect(mqtt_sink::MqttSinkConfig {
+      host: host.clone(),
+      port: mqtt_broker_port.unwrap_or(1883),
+      client_id: "start-connect-chatter".to_string(),
+      qos,
+    })
+    .await
+    .map_err(|e| format!("MQTT接続エラー: {}", e))?;
+
+    // 親（salesforce_id）の先頭3文字からオブジェクト種別を逆引きしてグループごとにパブリッシュする
+    let mut records_by_object_type: HashMap<String, Vec<&crate::csv::processor::ProcessableChatterRecord>> =
+      HashMap::new();
+    for record in &processable_records {
+      if record.salesforce_id.len() < 3 {
+        continue;
+      }
+      if let Some(mapping) = object_mappings.get(&record.salesforce_id[..3]) {
+        records_by_object_type
+          .entry(mapping.hubspot_object.clone())
+          .or_default()
+          .push(record);
+      }
+    }
+
+    for (object_type, records) in &records_by_object_type {
+      let owned_records: Vec<crate::csv::processor::ProcessableChatterRecord> = records
+        .iter()
+        .map(|record| crate::csv::processor::ProcessableChatterRecord {
+          salesforce_id: record.salesforce_id.clone(),
+          feed_items: record.feed_items.clone(),
+          most_reacted_comment_id: record.most_reacted_comment_id.clone(),
+        })
+        .collect();
+      sink
+        .publish_batch(object_type, &owned_records)
+        .await
+        .map_err(|e| format!("MQTTパブリッシュエラー: {}", e))?;
+    }
+
+    sink
+      .disconnect()
+      .await
+      .map_err(|e| format!("MQTT切断エラー: {}", e))?;
+  }
+
+  // 中断した移行を再実行した際に同じFeedItemを重複投稿しないよう、
+  // 親レコードごとの同期済み最新CreatedDateをチェックポイントから読み込む
+  let app_handle = window.app_handle();
+  let mut chatter_checkpoint =
+    ChatterCheckpoint::load(&app_handle).map_err(|e| format!("チェックポイント読み込みエラー: {}", e))?;
+
+  // ノート内容のハッシュと添付ファイルのアップロード結果を記録するレジャー。
+  // 再実行時、同じ(salesforce_id, FeedItem.id)で内容ハッシュが一致すればノート作成をスキップし、
+  // 同じ(content_document_id, version_id)の添付ファイルは再アップロードしない
+  let note_ledger = Arc::new(Mutex::new(
+    NoteLedger::load(&app_handle).map_err(|e| format!("ノートレジャー読み込みエラー: {}", e))?,
+  ));
+
+  if chatter_checkpoint.restored_count() > 0 {
+    emit_progress(
+      "cache_restore",
+      58,
+      &format!(
+        "キャッシュから{}件の親レコードの同期状況を復元しました",
+        chatter_checkpoint.restored_count()
+      ),
+      chatter_checkpoint.restored_count(),
+      processable_records.len(),
+    );
+  }
+
+  // ノート作成処理
+  let mut migration_cancelled = false;
+
+  /// 並行実行する1親レコード分のノート作成ジョブ
+  struct ChatterRecordJob {
+    salesforce_id: String,
+    prefix: String,
+    hubspot_object: String,
+    hubspot_record_id: String,
+    record_url: String,
+    total_feed_items: usize,
+    pending_feed_items: Vec<crate::csv::processor::FeedItemWithComments>,
+  }
+
+  /// パイプラインの結果
+  /// csv_writer/summariesへの書き込みは`.await`をまたがないよう、収集後にまとめて順番に適用する
+  struct ChatterRecordOutcome {
+    job: ChatterRecordJob,
+    notes_created: usize,
+    already_migrated: usize,
+    checkpoint_advances: Vec<String>,
+    cancelled: bool,
+  }
+
+  let mut record_jobs = Vec::new();
+
+  for record in &processable_records {
     if let Some(mapping) = object_mappings
       .iter()
       .find(|(prefix, _)| record.salesforce_id.starts_with(prefix.as_str()))
@@ -899,61 +2298,217 @@ pub async fn process_chatter_migration(
           skipped_count: 0,
           error_count: 0,
           uploaded_files: 0,
+          deduplicated_files: 0,
         });
 
+      // チェックポイントより新しいFeedItemのみを処理対象とする（既に同期済みのものはスキップ）
+      let pending_feed_items: Vec<crate::csv::processor::FeedItemWithComments> = record
+        .feed_items
+        .iter()
+        .filter(|feed_item_with_comments| {
+          chatter_checkpoint.is_new(
+            &record.salesforce_id,
+            &feed_item_with_comments.feed_item.created_date,
+          )
+        })
+        .cloned()
+        .collect();
+
+      if pending_feed_items.is_empty() {
+        // 全FeedItemがチェックポイント上で同期済みのため今回はスキップ
+        if let Some(summary) = summaries.get_mut(&record.salesforce_id[..3]) {
+          summary.skipped_count += 1;
+        }
+        let _ = csv_writer.write_record([
+          &record.salesforce_id,
+          &mapping.hubspot_object,
+          &hubspot_record_id,
+          &record_url,
+          &record.feed_items.len().to_string(),
+          "0",
+          "already_synced",
+          "",
+        ]);
+        continue;
+      }
+
+      record_jobs.push(ChatterRecordJob {
+        salesforce_id: record.salesforce_id.clone(),
+        prefix: record.salesforce_id[..3].to_string(),
+        hubspot_object: mapping.hubspot_object.clone(),
+        hubspot_record_id,
+        record_url,
+        total_feed_items: record.feed_items.len(),
+        pending_feed_items,
+      });
+    }
+  }
+
+  let total_record_jobs = record_jobs.len();
+  log::info!(
+    "{}件の親レコードをノート作成パイプラインへ投入（並行数: {}）",
+    total_record_jobs,
+    attachment_concurrency
+  );
+
+  let hubspot_service = Arc::new(hubspot_service);
+  let file_backend = crate::file_backend::build_file_backend(file_backend.as_ref(), hubspot_service.clone())
+    .await
+    .map_err(|e| e.to_string())?;
+  let file_info = Arc::new(file_info);
+  let users = Arc::new(users);
+  let window = Arc::new(window.clone());
+  let completed_record_jobs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+  let mut record_outcomes = stream::iter(record_jobs.into_iter().map(|job| {
+    let hubspot_service = hubspot_service.clone();
+    let file_backend = file_backend.clone();
+    let file_info = file_info.clone();
+    let users = users.clone();
+    let note_ledger = note_ledger.clone();
+    let cancelled_flag = cancelled_flag.clone();
+    let completed_record_jobs = completed_record_jobs.clone();
+    let window = window.clone();
+
+    async move {
+      // 各親レコードの処理開始前にキャンセル要求を確認する
+      if cancelled_flag.load(std::sync::atomic::Ordering::SeqCst) {
+        return ChatterRecordOutcome {
+          job,
+          notes_created: 0,
+          already_migrated: 0,
+          checkpoint_advances: Vec::new(),
+          cancelled: true,
+        };
+      }
+
       let mut notes_created = 0;
+      let mut already_migrated = 0;
+      let mut checkpoint_advances = Vec::new();
+
+      for feed_item_with_comments in &job.pending_feed_items {
+        // ノート内容のハッシュを先に計算し、レジャーに同一内容の作成済みノートが無いか確認する
+        // （if-none-matchと同じ考え方で、内容が変わっていなければ添付アップロードもノート作成もスキップする）
+        let note_html = generate_chatter_note_html(feed_item_with_comments, &users, &locale);
+        let content_hash = compute_content_hash(note_html.as_bytes());
+        let note_key = NoteLedger::note_key(&job.salesforce_id, &feed_item_with_comments.feed_item.id);
+
+        let already_migrated_hit = note_ledger
+          .lock()
+          .unwrap()
+          .lookup_note(&note_key)
+          .is_some_and(|entry| entry.content_hash == content_hash);
+
+        if already_migrated_hit {
+          already_migrated += 1;
+          checkpoint_advances.push(feed_item_with_comments.feed_item.created_date.clone());
+          continue;
+        }
 
-      for feed_item_with_comments in &record.feed_items {
-        // 添付ファイルをアップロード
-        let mut file_ids = Vec::new();
-        for content_doc_id in &feed_item_with_comments.attachment_content_document_ids {
-          if let Some(file_data) = file_info.get(content_doc_id) {
-            if let Some(version_data) = &file_data.version_data {
-              let filename = if let Some(dot_pos) = file_data.path_on_client.rfind('.') {
-                let (name, ext) = file_data.path_on_client.split_at(dot_pos);
-                format!("{}_{}{}", name, file_data.version_id, ext.to_lowercase())
-              } else {
-                format!("{}_{}", file_data.path_on_client, file_data.version_id)
-              };
-
-              match hubspot_service
-                .upload_file_from_base64(version_data, &filename)
-                .await
-              {
-                Ok(file_id) => {
-                  file_ids.push(file_id);
-                  log::debug!("アップロード成功: {}", filename);
-                }
-                Err(e) => {
-                  log::warn!("アップロード失敗 {}: {}", filename, e);
+        // 添付ファイルを並行でアップロード。content_document_id + version_idでレジャーを確認し、
+        // 既にアップロード済みのファイルは再アップロードしない
+        // （レジャーはHubSpotファイルIDのみを記録するため、重複排除が効くのは対応するバックエンドのみ）
+        let file_refs: Vec<crate::file_backend::FileRef> = stream::iter(
+          feed_item_with_comments
+            .feed_item_attachment_ids
+            .iter()
+            .filter_map(|content_doc_id| {
+              file_info
+                .get(content_doc_id)
+                .map(|file_data| (content_doc_id.clone(), file_data))
+            }),
+        )
+        .map(|(content_doc_id, file_data)| {
+          let cancelled_flag = cancelled_flag.clone();
+          let note_ledger = note_ledger.clone();
+          let file_backend = file_backend.clone();
+          async move {
+            // ファイルアップロードの合間にキャンセル要求を確認し、要求後は以降の添付ファイルをアップロードしない
+            if cancelled_flag.load(std::sync::atomic::Ordering::SeqCst) {
+              return None;
+            }
+
+            let version_data = file_data.version_data.as_ref()?;
+            let file_key = NoteLedger::file_key(&content_doc_id, &file_data.version_id);
+
+            if file_backend.supports_content_dedup() {
+              if let Some(existing_file_id) = note_ledger.lock().unwrap().lookup_file(&file_key) {
+                log::debug!("添付ファイルは登録済み、再アップロードをスキップ: {}", file_key);
+                return Some(crate::file_backend::FileRef::HubSpotFile(existing_file_id));
+              }
+            }
+
+            let filename = if let Some(dot_pos) = file_data.path_on_client.rfind('.') {
+              let (name, ext) = file_data.path_on_client.split_at(dot_pos);
+              format!("{}_{}{}", name, file_data.version_id, ext.to_lowercase())
+            } else {
+              format!("{}_{}", file_data.path_on_client, file_data.version_id)
+            };
+
+            let base64_data = match version_data.to_base64() {
+              Ok(data) => data,
+              Err(e) => {
+                log::warn!("バージョンデータ読み込み失敗 {}: {}", filename, e);
+                return None;
+              }
+            };
+
+            use base64::Engine;
+            let bytes = match base64::engine::general_purpose::STANDARD.decode(&base64_data) {
+              Ok(bytes) => bytes,
+              Err(e) => {
+                log::warn!("base64デコードエラー {}: {}", filename, e);
+                return None;
+              }
+            };
+
+            match file_backend.upload(bytes, &filename).await {
+              Ok(file_ref) => {
+                log::debug!("アップロード成功: {}", filename);
+                if let crate::file_backend::FileRef::HubSpotFile(file_id) = &file_ref {
+                  if file_backend.supports_content_dedup() {
+                    if let Err(e) = note_ledger
+                      .lock()
+                      .unwrap()
+                      .record_file(file_key, file_id.clone())
+                    {
+                      log::warn!("ノートレジャーへのファイル記録に失敗: {}", e);
+                    }
+                  }
                 }
+                Some(file_ref)
+              }
+              Err(e) => {
+                log::warn!("アップロード失敗 {}: {}", filename, e);
+                None
               }
             }
           }
-        }
-
-        let note_html = generate_chatter_note_html(feed_item_with_comments, &users);
+        })
+        .buffer_unordered(attachment_concurrency)
+        .filter_map(|result| async move { result })
+        .collect()
+        .await;
 
         match hubspot_service
-          .create_note_for_record(
-            &hubspot_record_id,
-            &mapping.hubspot_object,
-            &note_html,
-            if file_ids.is_empty() {
-              None
-            } else {
-              Some(file_ids)
-            },
-          )
+          .create_note_for_record(&job.hubspot_record_id, &job.hubspot_object, &note_html, &file_refs)
           .await
         {
-          Ok(_) => {
+          Ok(note_id) => {
             notes_created += 1;
+            if let Err(e) = note_ledger
+              .lock()
+              .unwrap()
+              .record_note(note_key, note_id, content_hash)
+            {
+              log::warn!("ノートレジャーへの記録に失敗: {}", e);
+            }
+            checkpoint_advances.push(feed_item_with_comments.feed_item.created_date.clone());
           }
           Err(e) => {
             log::error!(
               "ノート作成失敗 {} (FeedItem: {}): {}",
-              record.salesforce_id,
+              job.salesforce_id,
               feed_item_with_comments.feed_item.id,
               e
             );
@@ -961,61 +2516,178 @@ pub async fn process_chatter_migration(
         }
       }
 
-      let status = if notes_created == record.feed_items.len() {
-        if let Some(summary) = summaries.get_mut(&record.salesforce_id[..3]) {
-          summary.success_count += 1;
-          summary.uploaded_files += notes_created;
-        }
-        "success"
-      } else if notes_created > 0 {
-        if let Some(summary) = summaries.get_mut(&record.salesforce_id[..3]) {
-          summary.success_count += 1;
-          summary.uploaded_files += notes_created;
-        }
-        "partial"
-      } else {
-        if let Some(summary) = summaries.get_mut(&record.salesforce_id[..3]) {
-          summary.error_count += 1;
-        }
-        "error"
+      let done = completed_record_jobs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+      let progress = 60 + (30 * done / total_record_jobs.max(1)) as u8;
+      let progress_info = ProgressInfo {
+        step: "create_notes".to_string(),
+        progress,
+        message: format!("処理中 ({}/{})", done, total_record_jobs),
+        current_stage: stage_index(CHATTER_MIGRATION_STAGES, "create_notes"),
+        total_stages: CHATTER_MIGRATION_STAGES.len() as u8,
+        items_processed: done,
+        items_total: total_record_jobs,
+        estimated_seconds_remaining: estimate_seconds_remaining(start_time, done, total_record_jobs),
       };
-
+      let _ = window.emit("chatter-migration-progress", &progress_info);
+
+      ChatterRecordOutcome {
+        job,
+        notes_created,
+        already_migrated,
+        checkpoint_advances,
+        cancelled: false,
+      }
+    }
+  }))
+  .buffer_unordered(attachment_concurrency)
+  .collect::<Vec<_>>()
+  .await;
+
+  // 結果の書き込み順序を安定させるため、Salesforce IDの順に並べ直す
+  record_outcomes.sort_by_key(|o| o.job.salesforce_id.clone());
+
+  for outcome in record_outcomes {
+    let job = outcome.job;
+
+    if outcome.cancelled {
+      // キャンセル要求により処理をスキップしたレコード。次回実行時に再開できるようチェックポイントは進めない
+      migration_cancelled = true;
+      if let Some(summary) = summaries.get_mut(&job.prefix) {
+        summary.skipped_count += 1;
+      }
       let _ = csv_writer.write_record([
-        &record.salesforce_id,
-        &mapping.hubspot_object,
-        &hubspot_record_id,
-        &record_url,
-        &record.feed_items.len().to_string(),
-        &notes_created.to_string(),
-        status,
-        "",
+        &job.salesforce_id,
+        &job.hubspot_object,
+        &job.hubspot_record_id,
+        &job.record_url,
+        &job.total_feed_items.to_string(),
+        "0",
+        "cancelled",
+        "ユーザーによりキャンセルされました",
       ]);
+      continue;
+    }
+
+    for created_date in &outcome.checkpoint_advances {
+      chatter_checkpoint.advance(&job.salesforce_id, created_date);
+    }
+
+    let processed_without_error = outcome.notes_created + outcome.already_migrated;
+    let status = if processed_without_error == job.pending_feed_items.len() && outcome.already_migrated > 0 && outcome.notes_created == 0 {
+      if let Some(summary) = summaries.get_mut(&job.prefix) {
+        summary.success_count += 1;
+      }
+      "already_migrated"
+    } else if processed_without_error == job.pending_feed_items.len() {
+      if let Some(summary) = summaries.get_mut(&job.prefix) {
+        summary.success_count += 1;
+        summary.uploaded_files += outcome.notes_created;
+      }
+      "success"
+    } else if processed_without_error > 0 {
+      if let Some(summary) = summaries.get_mut(&job.prefix) {
+        summary.success_count += 1;
+        summary.uploaded_files += outcome.notes_created;
+      }
+      "partial"
+    } else {
+      if let Some(summary) = summaries.get_mut(&job.prefix) {
+        summary.error_count += 1;
+      }
+      "error"
+    };
+
+    let _ = csv_writer.write_record([
+      &job.salesforce_id,
+      &job.hubspot_object,
+      &job.hubspot_record_id,
+      &job.record_url,
+      &job.total_feed_items.to_string(),
+      &outcome.notes_created.to_string(),
+      status,
+      "",
+    ]);
+
+    // 親レコード単位でチェックポイントを永続化し、途中で中断しても再開時に重複投稿しないようにする
+    if let Err(e) = chatter_checkpoint.save() {
+      log::warn!("Chatterチェックポイント保存失敗: {}", e);
     }
   }
 
   csv_writer
     .flush()
     .map_err(|e| format!("CSVフラッシュエラー: {}", e))?;
-  emit_progress("complete", 100, "処理完了");
+  emit_progress(
+    "complete",
+    100,
+    "処理完了",
+    processable_records.len(),
+    processable_records.len(),
+  );
 
   log::info!("Chatter移行処理完了");
 
+  let summaries_vec: Vec<ObjectSummary> = summaries.into_values().collect();
+  let bundle_path = build_result_bundle(
+    &temp_dir,
+    "chatter_migration_bundle",
+    &result_csv_path,
+    None,
+    &summaries_vec,
+  )
+  .await;
+
   Ok(FileMappingResponse {
     result_csv_path: result_csv_path.to_string_lossy().to_string(),
-    summaries: summaries.into_values().collect(),
+    summaries: summaries_vec,
+    // Chatter移行はRecordResult形式の一覧をまだ構築していないため空のまま返す（結果は別途result_csv_pathで確認する）
+    records: Vec::new(),
+    cancelled: migration_cancelled,
+    // Chatter移行は別経路で添付ファイルを解決するため、このプリフライトスキャンの対象外
+    preflight: PreflightReport::default(),
+    // Chatter移行はRecordResult形式の一覧を構築していないため、JUnitレポートは出力せずパスは空文字とする
+    junit_report_path: String::new(),
+    bundle_path,
   })
 }
 
-/// ChatterノートのHTMLを生成
-fn generate_chatter_note_html(
-  feed_item_with_comments: &crate::csv::processor::FeedItemWithComments,
-  users: &HashMap<String, crate::csv::processor::UserRecord>,
-) -> String {
-  let feed_item = &feed_item_with_comments.feed_item;
-  let comments = &feed_item_with_comments.comments;
+/// Chatterノートを生成する際の表示言語。リクエストの`default_locale`で選択し、
+/// 未指定または未対応のコードの場合は英語にフォールバックする
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+  Ja,
+  En,
+}
+
+impl Locale {
+  /// "ja"/"ja-JP"のような言語コード文字列からLocaleを決定する
+  pub fn from_code(code: Option<&str>) -> Self {
+    match code.map(|c| c.to_lowercase()) {
+      Some(c) if c.starts_with("ja") => Locale::Ja,
+      _ => Locale::En,
+    }
+  }
 
-  // 日時を整形 (ISO 8601 -> 読みやすい形式)
-  let format_date = |date_str: &str| -> String {
+  /// このロケールのノート文言カタログ
+  fn messages(&self) -> NoteMessages {
+    match self {
+      Locale::Ja => NoteMessages {
+        chatter_post_label: "Chatter投稿",
+        comments_label: |count| format!("コメント ({}件)", count),
+        feed_item_id_label: "Salesforce FeedItem ID",
+      },
+      Locale::En => NoteMessages {
+        chatter_post_label: "Chatter Post",
+        comments_label: |count| format!("Comments ({})", count),
+        feed_item_id_label: "Salesforce FeedItem ID",
+      },
+    }
+  }
+
+  /// ISO 8601形式の日時をこのロケールの表示形式に整形する
+  fn format_date(&self, date_str: &str) -> String {
+    // 現状どのロケールも同じ整形方法だが、将来ロケールごとに日付表示を変える際の差し込み口として残す
     date_str
       .replace('T', " ")
       .replace('Z', "")
@@ -1023,7 +2695,43 @@ fn generate_chatter_note_html(
       .next()
       .unwrap_or(date_str)
       .to_string()
-  };
+  }
+}
+
+/// ノート生成で使う文言のカタログ（ロケールごとに`Locale::messages`が返す）
+struct NoteMessages {
+  chatter_post_label: &'static str,
+  comments_label: fn(usize) -> String,
+  feed_item_id_label: &'static str,
+}
+
+/// ノート本文に埋め込むことを許可するインラインタグ。`<a>`は意図的に含めない
+/// (@メンションを囲むアンカータグもここで剥がれ、メンション自体はプレーンテキストとして残る)
+const CHATTER_BODY_ALLOWED_TAGS: &[&str] = &["b", "strong", "i", "em", "u", "br", "p", "ul", "ol", "li", "blockquote"];
+
+/// Chatterの生本文(Markdown風の書式・生HTML・@メンションを含みうる)を、
+/// ノートHTMLへ安全に埋め込める形に変換する。
+/// comrakでMarkdown風の書式をHTML化し、ammoniaの許可リストでサニタイズすることで、
+/// 許可外のタグ(スクリプトや`<a>`などを含む)は取り除きテキストのみを残し、
+/// 周囲の`<div>`レイアウトが崩れたりHTMLインジェクションが起きたりしないようにする
+fn render_chatter_body(raw: &str) -> String {
+  let rendered_markdown = comrak::markdown_to_html(raw, &comrak::Options::default());
+
+  let mut builder = ammonia::Builder::default();
+  builder.tags(CHATTER_BODY_ALLOWED_TAGS.iter().copied().collect());
+
+  builder.clean(&rendered_markdown).to_string()
+}
+
+/// ChatterノートのHTMLを生成
+fn generate_chatter_note_html(
+  feed_item_with_comments: &crate::csv::processor::FeedItemWithComments,
+  users: &HashMap<String, crate::csv::processor::UserRecord>,
+  locale: &Locale,
+) -> String {
+  let feed_item = &feed_item_with_comments.feed_item;
+  let comments = &feed_item_with_comments.comments;
+  let messages = locale.messages();
 
   // ユーザー情報を取得して表示名を生成
   let format_user = |user_id: &str| -> String {
@@ -1037,7 +2745,10 @@ fn generate_chatter_note_html(
   let mut html = String::new();
 
   // ヘッダー
-  html.push_str("<p style=\"font-size: 10px; color: #999; margin: 0 0 12px 0;\">Chatter投稿</p>");
+  html.push_str(&format!(
+    "<p style=\"font-size: 10px; color: #999; margin: 0 0 12px 0;\">{}</p>",
+    messages.chatter_post_label
+  ));
 
   // 投稿本文
   html.push_str(
@@ -1045,28 +2756,28 @@ fn generate_chatter_note_html(
   );
   html.push_str(&format!(
     "<p style=\"font-size: 11px; color: #666; margin: 0 0 8px 0;\">{} - {}</p>",
-    format_date(&feed_item.created_date),
+    locale.format_date(&feed_item.created_date),
     format_user(&feed_item.created_by_id)
   ));
-  html.push_str(&feed_item.body);
+  html.push_str(&render_chatter_body(&feed_item.body));
   html.push_str("</div>");
 
   // コメント
   if !comments.is_empty() {
     html.push_str(&format!(
-      "<p style=\"font-size: 12px; font-weight: 600; margin: 16px 0 8px 0;\">コメント ({}件)</p>",
-      comments.len()
+      "<p style=\"font-size: 12px; font-weight: 600; margin: 16px 0 8px 0;\">{}</p>",
+      (messages.comments_label)(comments.len())
     ));
 
     for comment in comments {
       html.push_str("<div style=\"background: #fafafa; padding: 10px; border-radius: 4px; border-left: 3px solid #ccc; margin-top: 8px;\">");
       html.push_str(&format!(
         "<p style=\"font-size: 11px; color: #666; margin: 0 0 6px 0;\">{} - {}</p>",
-        format_date(&comment.created_date),
+        locale.format_date(&comment.created_date),
         format_user(&comment.created_by_id)
       ));
       html.push_str("<div style=\"font-size: 12px; line-height: 1.5;\">");
-      html.push_str(&comment.comment_body);
+      html.push_str(&render_chatter_body(&comment.comment_body));
       html.push_str("</div>");
       html.push_str("</div>");
     }
@@ -1075,8 +2786,8 @@ fn generate_chatter_note_html(
   // フッター
   html.push_str("<hr style=\"margin: 16px 0; border: none; border-top: 1px solid #e5e5e5;\">");
   html.push_str(&format!(
-    "<p style=\"font-size: 10px; color: #999; margin: 0;\">Salesforce FeedItem ID: {}</p>",
-    feed_item.id
+    "<p style=\"font-size: 10px; color: #999; margin: 0;\">{}: {}</p>",
+    messages.feed_item_id_label, feed_item.id
   ));
 
   html
@@ -1,9 +1,33 @@
 // CSV処理関連の機能を提供するモジュール
 use anyhow::{anyhow, Result};
 use csv::ReaderBuilder;
+use flate2::read::GzDecoder;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read};
 use std::path::Path;
+use tar::Archive;
+
+/// ChatterのFeedItem/FeedCommentを示すLinkedEntityIdプレフィックス
+const CHATTER_FEED_ITEM_PREFIX: &str = "0D5";
+const CHATTER_FEED_COMMENT_PREFIX: &str = "0D7";
+
+/// ContentDocumentLink.csvの1回の読み込みから導出できる全データをまとめた構造
+///
+/// `analyze_object_groups`/`extract_target_records`/`count_chatter_attachments`/
+/// `load_chatter_content_document_links`はいずれもこの走査結果の一部を切り出す薄いラッパー
+#[derive(Debug, Default)]
+pub struct ContentDocumentLinkScan {
+  /// プレフィックス別の出現件数
+  pub object_groups: HashMap<String, usize>,
+  /// オブジェクトマッピングに一致するプレフィックス別の(LinkedEntityId, ContentDocumentId)
+  pub target_records: HashMap<String, Vec<(String, String)>>,
+  /// (FeedItem添付件数, FeedComment添付件数)
+  pub chatter_attachment_counts: (usize, usize),
+  /// FeedItem/FeedCommentエンティティIdごとの添付ContentDocumentId
+  pub chatter_entity_links: HashMap<String, Vec<String>>,
+}
 
 /// ContentDocumentLinkのCSVレコード
 #[derive(Debug, Deserialize)]
@@ -33,6 +57,43 @@ pub struct ContentVersionRecord {
   pub version_data: Option<String>,
 }
 
+/// ファイルの実体データへの参照方法
+///
+/// CSVに埋め込まれたVersionDataは既にメモリ上にあるためそのまま保持するが、
+/// ContentVersionフォルダ上のファイルはパスだけを覚えておき、
+/// アップロード直前に読み込むことでエクスポート全体ではなく1ファイル分のメモリで済むようにする
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VersionData {
+  /// CSV内にbase64で埋め込まれたデータ
+  Inline(String),
+  /// ファイルシステム上のパス（読み込みはアップロード時まで遅延される）
+  OnDisk(std::path::PathBuf),
+}
+
+impl VersionData {
+  /// 生のバイト列を取得する（OnDiskの場合はここでファイルを読み込む）
+  pub fn read_bytes(&self) -> Result<Vec<u8>> {
+    match self {
+      VersionData::Inline(data) => Ok(base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD,
+        data,
+      )?),
+      VersionData::OnDisk(path) => Ok(std::fs::read(path)?),
+    }
+  }
+
+  /// アップロード用のbase64文字列を取得する
+  pub fn to_base64(&self) -> Result<String> {
+    match self {
+      VersionData::Inline(data) => Ok(data.clone()),
+      VersionData::OnDisk(path) => Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        std::fs::read(path)?,
+      )),
+    }
+  }
+}
+
 /// ファイル情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
@@ -40,8 +101,8 @@ pub struct FileInfo {
   pub version_id: String,
   /// クライアント上のパス
   pub path_on_client: String,
-  /// バージョンデータ（base64）
-  pub version_data: Option<String>,
+  /// バージョンデータ（base64埋め込み or ファイルシステム上のパス）
+  pub version_data: Option<VersionData>,
 }
 
 /// 処理可能なレコード情報
@@ -63,7 +124,7 @@ pub struct ObjectMapping {
 }
 
 /// ChatterFeedItemのCSVレコード
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ChatterFeedItemRecord {
   #[serde(rename = "Id")]
   pub id: String,
@@ -78,7 +139,7 @@ pub struct ChatterFeedItemRecord {
 }
 
 /// ChatterFeedCommentのCSVレコード
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ChatterCommentRecord {
   #[serde(rename = "Id")]
   #[allow(dead_code)]
@@ -120,72 +181,322 @@ pub struct FeedAttachmentRecord {
   pub attachment_type: String,
 }
 
+/// ChatterFeedLikeのCSVレコード（いいね/リアクション）
+#[derive(Debug, Deserialize, Clone)]
+pub struct FeedLikeRecord {
+  #[serde(rename = "Id")]
+  #[allow(dead_code)]
+  pub id: String,
+  #[serde(rename = "FeedEntityId")]
+  pub feed_entity_id: String,
+  #[serde(rename = "CreatedById")]
+  pub created_by_id: String,
+  #[serde(rename = "ReactionType", default)]
+  pub reaction_type: String,
+}
+
+/// FeedItem/コメント単位のエンゲージメント集計（いいね数・いいねしたユーザー・リアクション種別）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngagementInfo {
+  pub like_count: usize,
+  pub liking_user_ids: Vec<String>,
+  pub reaction_types: Vec<String>,
+}
+
 /// FeedItemとコメントをまとめた構造
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FeedItemWithComments {
   pub feed_item: ChatterFeedItemRecord,
   pub comments: Vec<ChatterCommentRecord>,
   pub feed_item_attachment_ids: Vec<String>,
   pub comment_attachments: HashMap<String, Vec<String>>,
+  pub feed_item_engagement: EngagementInfo,
+  /// コメントID -> エンゲージメント集計
+  pub comment_engagement: HashMap<String, EngagementInfo>,
+}
+
+/// Chatterレコード処理の挙動を制御する設定（TOML/JSON設定ファイルや環境変数から読み込む想定）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ChatterProcessingConfig {
+  /// trueならFeedItemを新しい順、falseなら古い順（従来通り）に並べる（FeedItem内のコメントは常に古い順）
+  pub sort_descending: bool,
+  /// この日時以降のCreatedDateを持つFeedItemのみを対象にする（未指定なら下限無し）
+  pub created_date_from: Option<String>,
+  /// この日時以前のCreatedDateを持つFeedItemのみを対象にする（未指定なら上限無し）
+  pub created_date_to: Option<String>,
+  /// コメント数がこの値未満のスレッドを除外する
+  pub min_comment_count: usize,
+  /// 親レコードあたりのFeedItem件数の上限（ソート後に先頭からこの件数のみ残す）
+  pub max_feed_items_per_parent: Option<usize>,
+}
+
+impl Default for ChatterProcessingConfig {
+  fn default() -> Self {
+    Self {
+      sort_descending: false,
+      created_date_from: None,
+      created_date_to: None,
+      min_comment_count: 0,
+      max_feed_items_per_parent: None,
+    }
+  }
+}
+
+impl ChatterProcessingConfig {
+  /// 指定したCreatedDateが設定された日時範囲に収まっているか
+  fn in_date_range(&self, created_date: &str) -> bool {
+    if let Some(from) = &self.created_date_from {
+      if created_date < from.as_str() {
+        return false;
+      }
+    }
+    if let Some(to) = &self.created_date_to {
+      if created_date > to.as_str() {
+        return false;
+      }
+    }
+    true
+  }
 }
 
 /// 処理可能なChatterレコード
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ProcessableChatterRecord {
   pub salesforce_id: String,
   pub feed_items: Vec<FeedItemWithComments>,
+  /// スレッド内で最もいいね数の多いコメントのID（いいねが1件も無ければNone）
+  pub most_reacted_comment_id: Option<String>,
+}
+
+/// 非標準なSalesforceエクスポート向けのCSV方言設定（区切り文字・クォート文字など）
+#[derive(Debug, Clone)]
+pub struct CsvDialect {
+  pub delimiter: u8,
+  pub quote: u8,
+  pub trim: bool,
+  pub flexible: bool,
 }
 
+impl Default for CsvDialect {
+  fn default() -> Self {
+    Self {
+      delimiter: b',',
+      quote: b'"',
+      trim: true,
+      flexible: true,
+    }
+  }
+}
+
+/// 先頭のUTF-8 BOM（U+FEFF）を取り除く。Excel等のエクスポートツールがBOM付きで出力することがあり、
+/// 放置すると最初のヘッダーカラム名がエイリアス表/正式名のいずれとも一致しなくなる
+fn strip_bom(s: &str) -> &str {
+  s.strip_prefix('\u{FEFF}').unwrap_or(s)
+}
+
+impl CsvDialect {
+  /// ヘッダー行中の区切り文字候補（, ; タブ |）の出現数を比較し、最も可能性の高いものを採用する
+  fn detect(header_line: &str) -> Self {
+    const CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+    let delimiter = CANDIDATES
+      .iter()
+      .copied()
+      .max_by_key(|&d| header_line.bytes().filter(|&b| b == d).count())
+      .filter(|&d| header_line.bytes().any(|b| b == d))
+      .unwrap_or(b',');
+
+    Self {
+      delimiter,
+      ..Self::default()
+    }
+  }
+}
+
+/// 非標準なヘッダー名から本来のSalesforceカラム名への対応表
+/// （エクスポートツールによっては人間可読な見出しで出力されることがある）
+const HEADER_ALIASES: &[(&str, &str)] = &[
+  ("Linked Entity ID", "LinkedEntityId"),
+  ("Link Entity Id", "LinkedEntityId"),
+  ("Content Document ID", "ContentDocumentId"),
+  ("Path On Client", "PathOnClient"),
+  ("Version Data", "VersionData"),
+  ("Parent ID", "ParentId"),
+  ("Created By ID", "CreatedById"),
+  ("Created Date", "CreatedDate"),
+  ("Feed Item ID", "FeedItemId"),
+  ("Comment Body", "CommentBody"),
+  ("Related Record ID", "RelatedRecordId"),
+  ("Feed Entity ID", "FeedEntityId"),
+  ("Record ID", "RecordId"),
+];
+
 /// CSV処理を行う構造体
 pub struct CsvProcessor;
 
 impl CsvProcessor {
-  /// ContentDocumentLink.csvからマッピング対象レコードを抽出
-  ///
-  /// # 引数
-  /// * `csv_path` - CSVファイルのパス
-  /// * `object_mappings` - オブジェクトマッピング設定
+  /// ヘッダー行をエイリアス表に基づいて正規化し、以降の`#[serde(rename = ...)]`による
+  /// デシリアライズがエイリアス名の列でも変わらず動作するようにする
+  fn normalize_headers(reader: &mut csv::Reader<Box<dyn Read>>) -> Result<()> {
+    let headers = reader.headers()?.clone();
+    let normalized: csv::StringRecord = headers
+      .iter()
+      .map(|h| {
+        HEADER_ALIASES
+          .iter()
+          .find(|(alias, _)| alias.eq_ignore_ascii_case(h))
+          .map(|(_, canonical)| *canonical)
+          .unwrap_or(h)
+      })
+      .collect();
+    reader.set_headers(normalized);
+    Ok(())
+  }
+
+  /// 拡張子に応じてプレーンCSV/gzip/tar.gzを透過的に解凍し、区切り文字の自動検出と
+  /// ヘッダー名のエイリアス正規化を行った上で読み込み用のReaderを構築する
   ///
-  /// # 戻り値
-  /// プレフィックス別にグループ化されたレコード情報
-  pub fn extract_target_records(
+  /// `.gz`はストリーミング展開、`.tar.gz`/`.tgz`はアーカイブ内の最初の`.csv`エントリを
+  /// メモリに展開して読み込む（tarはシーク不可のためストリーミング展開ができない）
+  fn open_csv_reader(csv_path: &str) -> Result<csv::Reader<Box<dyn Read>>> {
+    let lower = csv_path.to_lowercase();
+
+    let mut source: Box<dyn BufRead> = if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+      let file = File::open(csv_path)?;
+      let mut archive = Archive::new(GzDecoder::new(file));
+
+      let mut csv_bytes = None;
+      for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.to_path_buf();
+        if entry_path.extension().and_then(|e| e.to_str()) == Some("csv") {
+          let mut buf = Vec::new();
+          entry.read_to_end(&mut buf)?;
+          csv_bytes = Some(buf);
+          break;
+        }
+      }
+
+      let csv_bytes =
+        csv_bytes.ok_or_else(|| anyhow!("アーカイブ内にCSVファイルが見つかりません: {}", csv_path))?;
+      Box::new(Cursor::new(csv_bytes))
+    } else if lower.ends_with(".gz") {
+      let file = File::open(csv_path)?;
+      Box::new(BufReader::with_capacity(1 << 20, GzDecoder::new(file)))
+    } else {
+      let file = File::open(csv_path)?;
+      Box::new(BufReader::with_capacity(1 << 20, file))
+    };
+
+    // ヘッダー行を読み取って区切り文字を推定し、以降のストリームに読み戻す
+    let mut header_line = String::new();
+    source.read_line(&mut header_line)?;
+    // BOM付きUTF-8で出力するエクスポートツールがあるため、区切り文字検出/エイリアス照合の前に取り除く
+    header_line = strip_bom(&header_line).to_string();
+    let dialect = CsvDialect::detect(&header_line);
+
+    let chained: Box<dyn Read> = Box::new(Cursor::new(header_line.into_bytes()).chain(source));
+
+    let mut reader = ReaderBuilder::new()
+      .has_headers(true)
+      .delimiter(dialect.delimiter)
+      .quote(dialect.quote)
+      .trim(if dialect.trim {
+        csv::Trim::All
+      } else {
+        csv::Trim::None
+      })
+      .flexible(dialect.flexible)
+      .from_reader(chained);
+
+    Self::normalize_headers(&mut reader)?;
+    Ok(reader)
+  }
+
+  /// ContentDocumentLink.csvを1回読み込むだけで、分析・抽出の各処理が必要とする
+  /// データを同時に導出する（`object_mappings`/`target_feed_item_ids`は不要なら`None`でよい）
+  pub fn scan_content_document_link(
     csv_path: &str,
-    object_mappings: &HashMap<String, ObjectMapping>,
-  ) -> Result<HashMap<String, Vec<(String, String)>>> {
-    let mut records_by_type = HashMap::new();
+    object_mappings: Option<&HashMap<String, ObjectMapping>>,
+    target_feed_item_ids: Option<&HashSet<String>>,
+  ) -> Result<ContentDocumentLinkScan> {
+    let mut reader = Self::open_csv_reader(csv_path)?;
+    let mut scan = ContentDocumentLinkScan::default();
     let mut row_count = 0;
 
-    // CSVファイルを開いて読み込み
-    let mut reader = ReaderBuilder::new().has_headers(true).from_path(csv_path)?;
-
-    // 各行を処理
     for result in reader.deserialize() {
       row_count += 1;
       let record: ContentDocumentLinkRecord = result?;
 
-      // LinkedEntityIdが3文字以上の場合のみ処理
-      if record.linked_entity_id.len() >= 3 && !record.content_document_id.is_empty() {
-        let prefix = &record.linked_entity_id[..3];
+      if record.linked_entity_id.len() < 3 {
+        continue;
+      }
+      let prefix = &record.linked_entity_id[..3];
+
+      *scan.object_groups.entry(prefix.to_string()).or_insert(0) += 1;
 
-        // オブジェクトマッピングに存在するプレフィックスのみ処理
-        if object_mappings.contains_key(prefix) {
-          records_by_type
+      if prefix == CHATTER_FEED_ITEM_PREFIX {
+        scan.chatter_attachment_counts.0 += 1;
+      } else if prefix == CHATTER_FEED_COMMENT_PREFIX {
+        scan.chatter_attachment_counts.1 += 1;
+      }
+
+      if record.content_document_id.is_empty() {
+        continue;
+      }
+
+      if let Some(mappings) = object_mappings {
+        if mappings.contains_key(prefix) {
+          scan
+            .target_records
             .entry(prefix.to_string())
-            .or_insert_with(Vec::new)
-            .push((record.linked_entity_id, record.content_document_id));
+            .or_default()
+            .push((
+              record.linked_entity_id.clone(),
+              record.content_document_id.clone(),
+            ));
+        }
+      }
+
+      if let Some(target_ids) = target_feed_item_ids {
+        if (prefix == CHATTER_FEED_ITEM_PREFIX && target_ids.contains(&record.linked_entity_id))
+          || prefix == CHATTER_FEED_COMMENT_PREFIX
+        {
+          scan
+            .chatter_entity_links
+            .entry(record.linked_entity_id.clone())
+            .or_default()
+            .push(record.content_document_id.clone());
         }
       }
     }
 
-    log::info!("ContentDocumentLink.csv処理完了: {}行", row_count);
-    Ok(records_by_type)
+    log::info!("ContentDocumentLink.csv単一パス走査完了: {}行", row_count);
+    Ok(scan)
+  }
+
+  /// ContentDocumentLink.csvからマッピング対象レコードを抽出
+  ///
+  /// # 引数
+  /// * `csv_path` - CSVファイルのパス
+  /// * `object_mappings` - オブジェクトマッピング設定
+  ///
+  /// # 戻り値
+  /// プレフィックス別にグループ化されたレコード情報
+  ///
+  /// `scan_content_document_link`による単一パス走査の薄いラッパー
+  pub fn extract_target_records(
+    csv_path: &str,
+    object_mappings: &HashMap<String, ObjectMapping>,
+  ) -> Result<HashMap<String, Vec<(String, String)>>> {
+    Ok(Self::scan_content_document_link(csv_path, Some(object_mappings), None)?.target_records)
   }
 
   /// ContentVersion.csvからContentVersionId→ContentDocumentIdのマッピングを作成
   pub fn build_content_version_to_document_map(csv_path: &str) -> Result<HashMap<String, String>> {
     let mut version_to_document = HashMap::new();
 
-    let mut reader = ReaderBuilder::new().has_headers(true).from_path(csv_path)?;
+    let mut reader = Self::open_csv_reader(csv_path)?;
 
     for result in reader.deserialize() {
       let record: ContentVersionRecord = result?;
@@ -228,7 +539,7 @@ impl CsvProcessor {
     log::info!("対象ContentDocumentId: {}件", target_content_ids.len());
 
     // CSVファイルを読み込み
-    let mut reader = ReaderBuilder::new().has_headers(true).from_path(csv_path)?;
+    let mut reader = Self::open_csv_reader(csv_path)?;
 
     let mut file_info = HashMap::new();
     let mut found_content_ids = HashSet::new();
@@ -241,25 +552,16 @@ impl CsvProcessor {
 
       // 対象のContentDocumentIdの場合のみ処理
       if target_content_ids.contains(&record.content_document_id) {
-        let mut version_data = record.version_data;
+        let mut version_data = record.version_data.map(VersionData::Inline);
 
-        // VersionDataが空で、ContentVersionフォルダが指定されている場合
+        // VersionDataが空で、ContentVersionフォルダが指定されている場合は
+        // パスだけを記録し、実際の読み込みはアップロード時まで遅延する
         if version_data.is_none() && content_version_folder.is_some() {
           if let Some(folder) = content_version_folder {
             let file_path = Path::new(folder).join(&record.id);
             if file_path.exists() {
-              match std::fs::read(&file_path) {
-                Ok(file_bytes) => {
-                  version_data = Some(base64::Engine::encode(
-                    &base64::engine::general_purpose::STANDARD,
-                    file_bytes,
-                  ));
-                  log::debug!("ファイルシステムから読み込み: {}", file_path.display());
-                }
-                Err(e) => {
-                  log::warn!("ファイル読み込み失敗 {}: {}", file_path.display(), e);
-                }
-              }
+              log::debug!("ファイルシステム上に検出（遅延読み込み）: {}", file_path.display());
+              version_data = Some(VersionData::OnDisk(file_path));
             } else {
               log::debug!("ファイルが見つかりません: {}", file_path.display());
             }
@@ -310,6 +612,54 @@ impl CsvProcessor {
     Ok((file_info, filtered_target_records))
   }
 
+  /// 指定したContentDocumentIdの集合だけに絞ってContentVersion.csvからファイル情報を取得する
+  /// （`get_file_info_and_filter_records`と異なり、マッピング対象レコードの絞り込みは行わない）
+  pub fn load_file_info_for_ids(
+    csv_path: &str,
+    content_document_ids: &HashSet<String>,
+    content_version_folder: Option<&str>,
+  ) -> Result<HashMap<String, FileInfo>> {
+    let mut reader = Self::open_csv_reader(csv_path)?;
+    let mut file_info = HashMap::new();
+
+    for result in reader.deserialize() {
+      let record: ContentVersionRecord = result?;
+
+      if !content_document_ids.contains(&record.content_document_id) {
+        continue;
+      }
+
+      let mut version_data = record.version_data.map(VersionData::Inline);
+      if version_data.is_none() {
+        if let Some(folder) = content_version_folder {
+          let file_path = Path::new(folder).join(&record.id);
+          if file_path.exists() {
+            version_data = Some(VersionData::OnDisk(file_path));
+          }
+        }
+      }
+
+      let path_on_client = record
+        .path_on_client
+        .split('/')
+        .next_back()
+        .unwrap_or(&record.path_on_client)
+        .to_string();
+
+      file_info.insert(
+        record.content_document_id.clone(),
+        FileInfo {
+          version_id: record.id,
+          path_on_client,
+          version_data,
+        },
+      );
+    }
+
+    log::info!("ファイル情報取得: {}/{}件", file_info.len(), content_document_ids.len());
+    Ok(file_info)
+  }
+
   /// レコードをSalesforce ID別にグループ化
   ///
   /// # 引数
@@ -366,9 +716,7 @@ impl CsvProcessor {
 
     // ContentVersion.csvの必須カラムチェック
     let required_cv_columns = vec!["Id", "ContentDocumentId", "PathOnClient"];
-    let mut cv_reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(content_version_path)?;
+    let mut cv_reader = Self::open_csv_reader(content_version_path)?;
     let cv_headers = cv_reader.headers()?;
 
     for required_col in &required_cv_columns {
@@ -382,9 +730,7 @@ impl CsvProcessor {
 
     // ContentDocumentLink.csvの必須カラムチェック
     let required_cdl_columns = vec!["LinkedEntityId", "ContentDocumentId"];
-    let mut cdl_reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(content_document_link_path)?;
+    let mut cdl_reader = Self::open_csv_reader(content_document_link_path)?;
     let cdl_headers = cdl_reader.headers()?;
 
     for required_col in &required_cdl_columns {
@@ -401,49 +747,15 @@ impl CsvProcessor {
   }
 
   /// オブジェクトグループを分析
+  ///
+  /// `scan_content_document_link`による単一パス走査の薄いラッパー
   pub fn analyze_object_groups(content_document_link_path: &str) -> Result<HashMap<String, usize>> {
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(content_document_link_path)?;
-
-    let mut object_groups: HashMap<String, usize> = HashMap::new();
-    let mut total_records = 0;
-
-    // ヘッダーを取得してLinkedEntityIdのインデックスを特定
-    let headers = reader.headers()?.clone();
-    let linked_entity_id_index = headers
-      .iter()
-      .position(|h| h == "LinkedEntityId")
-      .ok_or_else(|| anyhow!("LinkedEntityIdカラムが見つかりません"))?;
-
-    for result in reader.records() {
-      let record = result?;
-      total_records += 1;
-
-      if let Some(linked_entity_id) = record.get(linked_entity_id_index) {
-        // 空文字や空白をチェック
-        let linked_entity_id = linked_entity_id.trim();
-        if !linked_entity_id.is_empty() && linked_entity_id.len() >= 3 {
-          let prefix = &linked_entity_id[0..3];
-          *object_groups.entry(prefix.to_string()).or_insert(0) += 1;
-        }
-      }
-    }
-
-    log::info!(
-      "ContentDocumentLink.csv分析完了: {}行、{}種類のオブジェクトを検出",
-      total_records,
-      object_groups.len()
-    );
-
-    Ok(object_groups)
+    Ok(Self::scan_content_document_link(content_document_link_path, None, None)?.object_groups)
   }
 
   /// Chatter FeedItem.csvを分析してParentIdでオブジェクトグループを取得
   pub fn analyze_chatter_object_groups(feed_item_path: &str) -> Result<HashMap<String, usize>> {
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(feed_item_path)?;
+    let mut reader = Self::open_csv_reader(feed_item_path)?;
 
     let mut object_groups: HashMap<String, usize> = HashMap::new();
     let mut total_records = 0;
@@ -486,9 +798,7 @@ impl CsvProcessor {
     let mut feed_items_by_prefix: HashMap<String, Vec<ChatterFeedItemRecord>> = HashMap::new();
 
     // FeedItem.csvを読み込み
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(feed_item_path)?;
+    let mut reader = Self::open_csv_reader(feed_item_path)?;
 
     for result in reader.deserialize() {
       let record: ChatterFeedItemRecord = result?;
@@ -519,9 +829,7 @@ impl CsvProcessor {
   ) -> Result<HashMap<String, Vec<ChatterCommentRecord>>> {
     let mut comments_by_feed_item: HashMap<String, Vec<ChatterCommentRecord>> = HashMap::new();
 
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(feed_comment_path)?;
+    let mut reader = Self::open_csv_reader(feed_comment_path)?;
 
     for result in reader.deserialize() {
       let record: ChatterCommentRecord = result?;
@@ -550,9 +858,7 @@ impl CsvProcessor {
       return Ok(users);
     }
 
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(user_path)?;
+    let mut reader = Self::open_csv_reader(user_path)?;
 
     for result in reader.deserialize() {
       let record: UserRecord = result?;
@@ -564,41 +870,13 @@ impl CsvProcessor {
   }
 
   /// ContentDocumentLinkでFeedItem/FeedCommentに紐づくファイル数をカウント
+  ///
+  /// `scan_content_document_link`による単一パス走査の薄いラッパー
   pub fn count_chatter_attachments(content_document_link_path: &str) -> Result<(usize, usize)> {
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(content_document_link_path)?;
-
-    let headers = reader.headers()?.clone();
-    let linked_entity_id_index = headers
-      .iter()
-      .position(|h| h == "LinkedEntityId")
-      .ok_or_else(|| anyhow!("LinkedEntityIdカラムが見つかりません"))?;
-
-    let mut feed_item_count = 0;
-    let mut feed_comment_count = 0;
-
-    for result in reader.records() {
-      let record = result?;
-      if let Some(linked_entity_id) = record.get(linked_entity_id_index) {
-        let linked_entity_id = linked_entity_id.trim();
-        if linked_entity_id.len() >= 3 {
-          let prefix = &linked_entity_id[0..3];
-          if prefix == "0D5" {
-            feed_item_count += 1;
-          } else if prefix == "0D7" {
-            feed_comment_count += 1;
-          }
-        }
-      }
-    }
-
-    log::info!(
-      "ContentDocumentLink分析: FeedItem={}, FeedComment={}",
-      feed_item_count,
-      feed_comment_count
-    );
-    Ok((feed_item_count, feed_comment_count))
+    Ok(
+      Self::scan_content_document_link(content_document_link_path, None, None)?
+        .chatter_attachment_counts,
+    )
   }
 
   /// FeedAttachmentを読み込んでFeedEntityIdでグループ化
@@ -615,9 +893,7 @@ impl CsvProcessor {
       return Ok(attachments_by_feed_item);
     }
 
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(feed_attachment_path)?;
+    let mut reader = Self::open_csv_reader(feed_attachment_path)?;
 
     for result in reader.deserialize() {
       let record: FeedAttachmentRecord = result?;
@@ -656,44 +932,56 @@ impl CsvProcessor {
     Ok(attachments_by_feed_item)
   }
 
-  /// ContentDocumentLinkからFeedItem/FeedCommentの添付ファイルを抽出
-  pub fn load_chatter_content_document_links(
-    content_document_link_path: &str,
-    target_feed_item_ids: &HashSet<String>,
-  ) -> Result<HashMap<String, Vec<String>>> {
-    let mut links_by_entity: HashMap<String, Vec<String>> = HashMap::new();
+  /// FeedLikeを読み込んでFeedEntityId（FeedItemまたはFeedCommentのID）ごとに集計
+  pub fn load_feed_likes(feed_like_path: &str) -> Result<HashMap<String, EngagementInfo>> {
+    let mut engagement_by_entity: HashMap<String, EngagementInfo> = HashMap::new();
 
-    if content_document_link_path.is_empty() || !Path::new(content_document_link_path).exists() {
-      log::info!("ContentDocumentLink.csvが指定されていないためスキップ");
-      return Ok(links_by_entity);
+    if feed_like_path.is_empty() || !Path::new(feed_like_path).exists() {
+      log::info!("FeedLike.csvが指定されていないためスキップ");
+      return Ok(engagement_by_entity);
     }
 
-    let mut reader = ReaderBuilder::new()
-      .has_headers(true)
-      .from_path(content_document_link_path)?;
+    let mut reader = Self::open_csv_reader(feed_like_path)?;
 
     for result in reader.deserialize() {
-      let record: ContentDocumentLinkRecord = result?;
-
-      // FeedItem(0D5)またはFeedComment(0D7)のみ処理
-      if record.linked_entity_id.len() >= 3 {
-        let prefix = &record.linked_entity_id[0..3];
-        if (prefix == "0D5" && target_feed_item_ids.contains(&record.linked_entity_id))
-          || prefix == "0D7"
-        {
-          links_by_entity
-            .entry(record.linked_entity_id.clone())
-            .or_default()
-            .push(record.content_document_id);
-        }
+      let record: FeedLikeRecord = result?;
+      let entry = engagement_by_entity
+        .entry(record.feed_entity_id.clone())
+        .or_default();
+      entry.like_count += 1;
+      entry.liking_user_ids.push(record.created_by_id);
+      if !record.reaction_type.is_empty() {
+        entry.reaction_types.push(record.reaction_type);
       }
     }
 
     log::info!(
-      "ContentDocumentLink読み込み完了: {}件のエンティティに添付",
-      links_by_entity.len()
+      "FeedLike読み込み完了: {}件のFeedItem/コメントにいいね",
+      engagement_by_entity.len()
     );
-    Ok(links_by_entity)
+    Ok(engagement_by_entity)
+  }
+
+  /// ContentDocumentLinkからFeedItem/FeedCommentの添付ファイルを抽出
+  ///
+  /// `scan_content_document_link`による単一パス走査の薄いラッパー
+  pub fn load_chatter_content_document_links(
+    content_document_link_path: &str,
+    target_feed_item_ids: &HashSet<String>,
+  ) -> Result<HashMap<String, Vec<String>>> {
+    if content_document_link_path.is_empty() || !Path::new(content_document_link_path).exists() {
+      log::info!("ContentDocumentLink.csvが指定されていないためスキップ");
+      return Ok(HashMap::new());
+    }
+
+    Ok(
+      Self::scan_content_document_link(
+        content_document_link_path,
+        None,
+        Some(target_feed_item_ids),
+      )?
+      .chatter_entity_links,
+    )
   }
 
   /// FeedItemとCommentを結合してProcessableChatterRecordを生成
@@ -704,6 +992,8 @@ impl CsvProcessor {
     content_document_links: HashMap<String, Vec<String>>,
     feed_attachments: HashMap<String, Vec<String>>,
     content_version_to_document: &HashMap<String, String>,
+    feed_likes: &HashMap<String, EngagementInfo>,
+    config: &ChatterProcessingConfig,
   ) -> Vec<ProcessableChatterRecord> {
     let mut processable_records = Vec::new();
 
@@ -712,6 +1002,11 @@ impl CsvProcessor {
 
     for feed_items in feed_items_by_prefix.values() {
       for feed_item in feed_items {
+        // 設定された日時範囲外のFeedItemは対象外
+        if !config.in_date_range(&feed_item.created_date) {
+          continue;
+        }
+
         // HubSpotに存在するレコードのみ処理
         if found_hubspot_records.contains_key(&feed_item.parent_id) {
           let mut comments = comments_by_feed_item
@@ -787,6 +1082,22 @@ impl CsvProcessor {
             comment_attachments
           );
 
+          // エンゲージメント（いいね/リアクション）をFeedItem本体とコメントそれぞれに紐づける
+          let feed_item_engagement = feed_likes.get(&feed_item.id).cloned().unwrap_or_default();
+          let comment_engagement: HashMap<String, EngagementInfo> = comments
+            .iter()
+            .filter_map(|comment| {
+              feed_likes
+                .get(&comment.id)
+                .map(|engagement| (comment.id.clone(), engagement.clone()))
+            })
+            .collect();
+
+          // 最小コメント数フィルターで空スレッドを除外
+          if comments.len() < config.min_comment_count {
+            continue;
+          }
+
           records_by_parent
             .entry(feed_item.parent_id.clone())
             .or_default()
@@ -795,6 +1106,8 @@ impl CsvProcessor {
               comments,
               feed_item_attachment_ids,
               comment_attachments,
+              feed_item_engagement,
+              comment_engagement,
             });
         }
       }
@@ -802,12 +1115,32 @@ impl CsvProcessor {
 
     // ProcessableChatterRecordに変換
     for (salesforce_id, mut feed_items) in records_by_parent {
-      // FeedItemを日付でソート（古い順）
-      feed_items.sort_by(|a, b| a.feed_item.created_date.cmp(&b.feed_item.created_date));
+      // FeedItemを設定に応じた並び順でソート（デフォルトは古い順）
+      feed_items.sort_by(|a, b| {
+        if config.sort_descending {
+          b.feed_item.created_date.cmp(&a.feed_item.created_date)
+        } else {
+          a.feed_item.created_date.cmp(&b.feed_item.created_date)
+        }
+      });
+
+      // 親レコードあたりのFeedItem件数の上限を適用
+      if let Some(max_items) = config.max_feed_items_per_parent {
+        feed_items.truncate(max_items);
+      }
+
+      // スレッド全体で最もいいね数の多いコメントを集計
+      let most_reacted_comment_id = feed_items
+        .iter()
+        .flat_map(|item| item.comment_engagement.iter())
+        .filter(|(_, engagement)| engagement.like_count > 0)
+        .max_by_key(|(_, engagement)| engagement.like_count)
+        .map(|(comment_id, _)| comment_id.clone());
 
       processable_records.push(ProcessableChatterRecord {
         salesforce_id,
         feed_items,
+        most_reacted_comment_id,
       });
     }
 
@@ -815,3 +1148,59 @@ impl CsvProcessor {
     processable_records
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn strip_bom_removes_leading_utf8_bom() {
+    assert_eq!(strip_bom("\u{FEFF}Id,Name"), "Id,Name");
+  }
+
+  #[test]
+  fn strip_bom_is_noop_without_bom() {
+    assert_eq!(strip_bom("Id,Name"), "Id,Name");
+  }
+
+  #[test]
+  fn dialect_detects_comma() {
+    assert_eq!(CsvDialect::detect("Id,Name,PathOnClient").delimiter, b',');
+  }
+
+  #[test]
+  fn dialect_detects_semicolon() {
+    assert_eq!(CsvDialect::detect("Id;Name;PathOnClient").delimiter, b';');
+  }
+
+  #[test]
+  fn dialect_detects_tab() {
+    assert_eq!(CsvDialect::detect("Id\tName\tPathOnClient").delimiter, b'\t');
+  }
+
+  #[test]
+  fn dialect_detects_pipe() {
+    assert_eq!(CsvDialect::detect("Id|Name|PathOnClient").delimiter, b'|');
+  }
+
+  #[test]
+  fn dialect_falls_back_to_comma_without_a_delimiter_candidate() {
+    assert_eq!(CsvDialect::detect("SingleColumnHeader").delimiter, b',');
+  }
+
+  #[test]
+  fn normalize_headers_maps_known_aliases_case_insensitively() {
+    let data = "linked entity id,content document id,Unrelated\nA,B,C\n";
+    let mut reader = ReaderBuilder::new()
+      .has_headers(true)
+      .from_reader(Box::new(Cursor::new(data.as_bytes().to_vec())) as Box<dyn Read>);
+
+    CsvProcessor::normalize_headers(&mut reader).unwrap();
+
+    let headers = reader.headers().unwrap().clone();
+    assert_eq!(
+      headers.iter().collect::<Vec<_>>(),
+      vec!["LinkedEntityId", "ContentDocumentId", "Unrelated"]
+    );
+  }
+}
@@ -0,0 +1,219 @@
+// 添付ファイルのアップロード先を抽象化するモジュール。HubSpot Files APIのサイズ上限を超える
+// Salesforce添付ファイルを、顧客自身のオブジェクトストレージに退避できるようにする
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// ノートへの添付方法が異なる2種類のアップロード結果
+/// （HubSpotネイティブファイルはhs_attachment_idsで、外部ファイルは本文中のリンクで参照する）
+#[derive(Debug, Clone)]
+pub enum FileRef {
+  /// HubSpot Files APIにアップロード済みのネイティブファイルID
+  HubSpotFile(String),
+  /// 外部オブジェクトストレージに退避したファイルのURL
+  External { url: String, filename: String },
+}
+
+/// 添付ファイルのアップロード先バックエンド
+#[async_trait]
+pub trait FileBackend: Send + Sync {
+  /// バイト列をアップロードし、ノートに添付するための参照を返す
+  async fn upload(&self, bytes: Vec<u8>, filename: &str) -> Result<FileRef>;
+
+  /// このバックエンドに適用するアップロードサイズの上限（バイト）。上限が無ければ`None`
+  /// （HubSpot Files APIのみサイズ上限を持つため、他のバックエンドはデフォルトのまま上限無しでよい）
+  fn max_upload_bytes(&self) -> Option<u64> {
+    None
+  }
+
+  /// 同一パスに既存ファイルが無いか確認する。対応しないバックエンドはデフォルトのまま`None`を返せばよい
+  async fn find_by_path(&self, _path: &str) -> Result<Option<FileRef>> {
+    Ok(None)
+  }
+
+  /// コンテンツハッシュによる重複排除インデックスを利用できるか
+  /// （インデックスはHubSpotファイルIDのみを記録するため、対応するのは既定でHubSpotバックエンドのみ）
+  fn supports_content_dedup(&self) -> bool {
+    false
+  }
+
+  /// アップロード直後に保存先へ再問い合わせしてバイト数を検証する
+  /// （送信元バイト列との整合性チェック用。対応しないバックエンドはデフォルトのまま`None`を返せばよい）
+  async fn verify_uploaded_size(&self, _file_ref: &FileRef) -> Result<Option<u64>> {
+    Ok(None)
+  }
+}
+
+/// HubSpot Files APIへアップロードするデフォルトのバックエンド
+pub struct HubSpotFileBackend {
+  service: Arc<crate::hubspot::HubSpotService>,
+}
+
+impl HubSpotFileBackend {
+  pub fn new(service: Arc<crate::hubspot::HubSpotService>) -> Self {
+    Self { service }
+  }
+}
+
+#[async_trait]
+impl FileBackend for HubSpotFileBackend {
+  async fn upload(&self, bytes: Vec<u8>, filename: &str) -> Result<FileRef> {
+    let base64_data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+    let file_id = self
+      .service
+      .upload_file_from_base64(&base64_data, filename)
+      .await?;
+    Ok(FileRef::HubSpotFile(file_id))
+  }
+
+  fn max_upload_bytes(&self) -> Option<u64> {
+    Some(crate::hubspot::MAX_FILE_SIZE_BYTES)
+  }
+
+  async fn find_by_path(&self, path: &str) -> Result<Option<FileRef>> {
+    Ok(
+      self
+        .service
+        .get_file_by_path(path)
+        .await?
+        .map(|file| FileRef::HubSpotFile(file.id)),
+    )
+  }
+
+  fn supports_content_dedup(&self) -> bool {
+    true
+  }
+
+  async fn verify_uploaded_size(&self, file_ref: &FileRef) -> Result<Option<u64>> {
+    match file_ref {
+      FileRef::HubSpotFile(file_id) => {
+        Ok(self.service.get_file_metadata(file_id).await?.and_then(|m| m.size))
+      }
+      FileRef::External { .. } => Ok(None),
+    }
+  }
+}
+
+/// S3互換オブジェクトストレージ（S3・MinIO等）へアップロードするバックエンド。HubSpotのアップロード
+/// サイズ上限を超える添付ファイルを、顧客自身の保持ポリシーが及ぶバケットに退避するために使う
+pub struct S3FileBackend {
+  client: aws_sdk_s3::Client,
+  bucket: String,
+  /// アップロード先URLを組み立てる際のベースURL（CDN経由などバケット直リンクと異なる場合に指定）
+  public_base_url: Option<String>,
+}
+
+impl S3FileBackend {
+  pub fn new(client: aws_sdk_s3::Client, bucket: String, public_base_url: Option<String>) -> Self {
+    Self {
+      client,
+      bucket,
+      public_base_url,
+    }
+  }
+
+  /// バケット内でのキーの衝突を避けるため、ファイル名にランダムな接頭辞を付ける
+  fn object_key(filename: &str) -> String {
+    format!("salesforce-attachments/{}-{}", uuid::Uuid::new_v4(), filename)
+  }
+
+  fn object_url(&self, key: &str) -> String {
+    match &self.public_base_url {
+      Some(base) => format!("{}/{}", base.trim_end_matches('/'), key),
+      None => format!("https://{}.s3.amazonaws.com/{}", self.bucket, key),
+    }
+  }
+
+  /// `object_url`の逆変換。アップロード直後の整合性検証でバケットキーを取り戻すために使う
+  fn key_from_url<'a>(&self, url: &'a str) -> Option<&'a str> {
+    let prefix = match &self.public_base_url {
+      Some(base) => format!("{}/", base.trim_end_matches('/')),
+      None => format!("https://{}.s3.amazonaws.com/", self.bucket),
+    };
+    url.strip_prefix(prefix.as_str())
+  }
+}
+
+#[async_trait]
+impl FileBackend for S3FileBackend {
+  async fn upload(&self, bytes: Vec<u8>, filename: &str) -> Result<FileRef> {
+    let key = Self::object_key(filename);
+
+    self
+      .client
+      .put_object()
+      .bucket(&self.bucket)
+      .key(&key)
+      .body(bytes.into())
+      .send()
+      .await
+      .with_context(|| format!("S3へのアップロードに失敗しました: {}", key))?;
+
+    Ok(FileRef::External {
+      url: self.object_url(&key),
+      filename: filename.to_string(),
+    })
+  }
+
+  async fn verify_uploaded_size(&self, file_ref: &FileRef) -> Result<Option<u64>> {
+    let FileRef::External { url, .. } = file_ref else {
+      return Ok(None);
+    };
+    let Some(key) = self.key_from_url(url) else {
+      return Ok(None);
+    };
+
+    let head = self
+      .client
+      .head_object()
+      .bucket(&self.bucket)
+      .key(key)
+      .send()
+      .await
+      .with_context(|| format!("S3のアップロード後整合性確認に失敗しました: {}", key))?;
+
+    Ok(head.content_length().map(|len| len as u64))
+  }
+}
+
+/// コマンド引数として渡されるバックエンド選択設定。未指定時は`HubSpotFileBackend`を使う
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileBackendConfig {
+  /// `"s3"`を指定するとS3互換バケットへアップロードする。それ以外（未指定含む）はHubSpot Files API
+  pub kind: String,
+  pub s3_bucket: Option<String>,
+  pub s3_region: Option<String>,
+  /// アップロード先URLを組み立てる際のベースURL（CDN経由などバケット直リンクと異なる場合に指定）
+  pub s3_public_base_url: Option<String>,
+}
+
+/// 設定に応じてアップロード先バックエンドを構築する。未指定時は従来どおりHubSpot Files APIを使う
+pub async fn build_file_backend(
+  config: Option<&FileBackendConfig>,
+  hubspot_service: Arc<crate::hubspot::HubSpotService>,
+) -> Result<Arc<dyn FileBackend>> {
+  match config {
+    Some(config) if config.kind == "s3" => {
+      let bucket = config
+        .s3_bucket
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("S3バックエンドにはs3_bucketの指定が必要です"))?;
+
+      let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+      if let Some(region) = &config.s3_region {
+        loader = loader.region(aws_sdk_s3::config::Region::new(region.clone()));
+      }
+      let sdk_config = loader.load().await;
+      let client = aws_sdk_s3::Client::new(&sdk_config);
+
+      Ok(Arc::new(S3FileBackend::new(
+        client,
+        bucket,
+        config.s3_public_base_url.clone(),
+      )))
+    }
+    _ => Ok(Arc::new(HubSpotFileBackend::new(hubspot_service))),
+  }
+}
@@ -0,0 +1,696 @@
+// HubSpot API関連の処理を行うモジュール
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use futures::stream::{self, StreamExt};
+use governor::{Quota, RateLimiter};
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// HTML属性値/テキストとして安全に埋め込めるようエスケープする
+/// （外部ストレージのURL・ファイル名はSalesforce側の任意の文字列であり、そのまま埋め込むと
+/// `"`や`<`を含む値でノート本文にHTMLインジェクションが起きうる）
+fn escape_html(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
+
+/// レート制限または一時的なサーバーエラー時の最大試行回数（初回のリクエストを含む）のデフォルト値
+const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// `Retry-After`が無い場合の指数バックオフの基準遅延（ミリ秒）
+const DEFAULT_BASE_DELAY_MS: u64 = 500;
+
+/// 指数バックオフの上限（ミリ秒）。attemptが大きくなっても待ち時間が際限なく伸びないようにする
+const MAX_BACKOFF_DELAY_MS: u64 = 30_000;
+
+/// `batch_find_records`が同時に投げるチャンク検索の数のデフォルト値。
+/// レート制限のバースト上限を超えないよう`rate_limiter`と併用する
+const DEFAULT_SEARCH_CONCURRENCY: usize = 5;
+
+/// HubSpotの標準API制限（10秒あたりのリクエスト数）のデフォルト値。
+/// 大規模なChatter移行ではポータルのプラン上限に合わせてより保守的な値に調整できるようにする
+const DEFAULT_RATE_LIMIT_PER_10S: u32 = 100;
+
+/// HubSpot Files APIがドキュメントで公開しているファイルサイズ上限（バイト）
+/// これを超えるファイルはアップロードを試みる前に弾く
+pub const MAX_FILE_SIZE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// HubSpotサービス構造体
+/// APIトークンとHTTPクライアントを管理
+pub struct HubSpotService {
+  /// HubSpot APIアクセストークン
+  token: String,
+  /// HTTP通信用クライアント
+  client: Client,
+  /// バッチ処理の合間に入れる遅延時間（ミリ秒）
+  rate_limit_delay: u64,
+  /// HubSpotの10秒あたりのリクエスト数上限を超えないよう、全APIコールの前にここで間引く
+  rate_limiter: governor::DefaultDirectRateLimiter,
+  /// レート制限または一時的なサーバーエラー時の最大試行回数（初回のリクエストを含む）
+  max_retries: u32,
+  /// `Retry-After`が無い場合の指数バックオフの基準遅延（ミリ秒）
+  base_delay: u64,
+  /// `batch_find_records`で同時に検索するチャンク数の上限
+  search_concurrency: usize,
+}
+
+/// HubSpotレコード検索結果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+  /// 検索結果のレコード一覧
+  pub results: Vec<HubSpotRecord>,
+  /// 次ページの情報。無ければ最終ページ
+  #[serde(default)]
+  pub paging: Option<Paging>,
+}
+
+/// ページングカーソル情報
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Paging {
+  pub next: Option<NextPage>,
+}
+
+/// 次ページを取得するためのカーソル
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NextPage {
+  pub after: String,
+}
+
+/// HubSpotレコード情報
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HubSpotRecord {
+  /// レコードID
+  pub id: String,
+  /// プロパティ情報
+  pub properties: HashMap<String, String>,
+}
+
+/// ファイル情報構造体
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileInfo {
+  /// ファイルID
+  pub id: String,
+  /// ファイル名
+  pub name: String,
+  /// ファイルパス
+  pub path: String,
+  /// ファイルURL
+  pub url: Option<String>,
+}
+
+/// アップロード済みファイルのメタデータ（整合性検証用）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileMetadata {
+  /// ファイルID
+  pub id: String,
+  /// HubSpot側が報告するファイルサイズ（バイト）
+  pub size: Option<u64>,
+}
+
+/// HubSpotオブジェクト情報
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HubSpotObjectInfo {
+  /// オブジェクトタイプID
+  pub id: String,
+  /// オブジェクト名
+  pub name: String,
+  /// ラベル
+  pub labels: ObjectLabels,
+}
+
+/// オブジェクトラベル
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ObjectLabels {
+  /// 単数形ラベル
+  pub singular: String,
+  /// 複数形ラベル
+  pub plural: String,
+}
+
+/// HubSpotアカウント詳細情報
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountDetails {
+  #[serde(rename = "portalId")]
+  pub portal_id: u64,
+  #[serde(rename = "accountType")]
+  pub account_type: String,
+  #[serde(rename = "timeZone")]
+  pub time_zone: String,
+  #[serde(rename = "companyCurrency")]
+  pub company_currency: String,
+  #[serde(rename = "uiDomain")]
+  pub ui_domain: String,
+  #[serde(rename = "dataHostingLocation")]
+  pub data_hosting_location: String,
+}
+
+/// スキーマAPIレスポンス
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchemaResponse {
+  /// 結果一覧
+  pub results: Vec<HubSpotObjectInfo>,
+  /// 次ページの情報。無ければ最終ページ
+  #[serde(default)]
+  pub paging: Option<Paging>,
+}
+
+/// ノート作成用のリクエストデータ
+#[derive(Debug, Serialize)]
+struct CreateNoteRequest {
+  /// ノートのプロパティ
+  properties: NoteProperties,
+  /// 関連付け情報
+  associations: Vec<Association>,
+}
+
+/// ノートのプロパティ
+#[derive(Debug, Serialize)]
+struct NoteProperties {
+  /// ノート本文
+  hs_note_body: String,
+  /// タイムスタンプ
+  hs_timestamp: String,
+  /// 添付ファイルID（セミコロン区切り）
+  hs_attachment_ids: Option<String>,
+}
+
+/// 関連付け情報
+#[derive(Debug, Serialize)]
+struct Association {
+  /// 関連付け先
+  to: AssociationTarget,
+  /// 関連付けタイプ
+  types: Vec<AssociationType>,
+}
+
+/// 関連付け先
+#[derive(Debug, Serialize)]
+struct AssociationTarget {
+  /// 関連付け先のID
+  id: String,
+}
+
+/// 関連付けタイプ
+#[derive(Debug, Serialize)]
+struct AssociationType {
+  /// 関連付けカテゴリ
+  #[serde(rename = "associationCategory")]
+  association_category: String,
+  /// 関連付けタイプID
+  #[serde(rename = "associationTypeId")]
+  association_type_id: u32,
+}
+
+/// `Retry-After`ヘッダーを解析する。秒数表記とHTTP-date表記（RFC 7231）の両方に対応し、
+/// 過去日時や解析失敗の場合は`None`（呼び出し側は指数バックオフにフォールバックする）
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+  let value = response.headers().get("Retry-After")?.to_str().ok()?;
+
+  if let Ok(secs) = value.trim().parse::<u64>() {
+    return Some(Duration::from_secs(secs));
+  }
+
+  let target = httpdate::parse_http_date(value.trim()).ok()?;
+  target.duration_since(SystemTime::now()).ok()
+}
+
+impl HubSpotService {
+  /// 新しいHubSpotServiceインスタンスを作成（デフォルトのレート制限・リトライ設定を使用）
+  pub fn new(token: String) -> Self {
+    Self::with_config(token, DEFAULT_RATE_LIMIT_PER_10S, DEFAULT_MAX_RETRIES)
+  }
+
+  /// レート制限のクォータ（10秒あたりのリクエスト数）と最大試行回数（初回のリクエストを含む）を指定してインスタンスを作成する。
+  /// 大規模なChatter移行でポータルのプラン上限がデフォルトより厳しい場合に使用する
+  pub fn with_config(token: String, requests_per_10s: u32, max_retries: u32) -> Self {
+    let burst = NonZeroU32::new(requests_per_10s.max(1)).unwrap();
+    let replenish_interval = (Duration::from_secs(10) / burst.get()).max(Duration::from_millis(1));
+    let quota = Quota::with_period(replenish_interval).unwrap().allow_burst(burst);
+
+    Self {
+      token,
+      client: Client::new(),
+      rate_limit_delay: 100, // 100ms
+      rate_limiter: RateLimiter::direct(quota),
+      max_retries: max_retries.max(1),
+      base_delay: DEFAULT_BASE_DELAY_MS,
+      search_concurrency: DEFAULT_SEARCH_CONCURRENCY,
+    }
+  }
+
+  /// 429（レート制限）・5xx（一時的なサーバーエラー）を検知した場合に
+  /// `Retry-After`（秒数またはHTTP-date）を優先し、無ければ完全ジッター付き指数バックオフ
+  /// （`random(0, min(cap, base * 2^attempt))`）で次のリトライまで待つ。4xx（429以外）は
+  /// リトライ対象外として即座に返す。リクエスト自体は、HubSpotのレート制限を超えないよう
+  /// あらかじめ`rate_limiter`で間引いてから送信する
+  /// `request` はリトライのたびに新しいリクエストを組み立てて送信するクロージャ
+  async fn send_with_retry<F, Fut>(&self, mut request: F) -> Result<Response>
+  where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<Response, reqwest::Error>>,
+  {
+    let mut attempt: u32 = 0;
+
+    loop {
+      self.rate_limiter.until_ready().await;
+
+      let response = request().await?;
+      let status = response.status();
+      let is_retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+
+      if !is_retryable {
+        return Ok(response);
+      }
+
+      attempt += 1;
+      if attempt >= self.max_retries {
+        return Err(anyhow!(
+          "HubSpot API呼び出しが{}回試行しても成功しませんでした（最終ステータス: {}）",
+          attempt,
+          status
+        ));
+      }
+
+      let delay = match parse_retry_after(&response) {
+        Some(retry_after) => retry_after,
+        None => {
+          // 完全ジッター: random(0, min(cap, base * 2^attempt))
+          let exponential_ms = self
+            .base_delay
+            .saturating_mul(2u64.saturating_pow(attempt));
+          let capped_ms = exponential_ms.min(MAX_BACKOFF_DELAY_MS);
+          Duration::from_millis(rand::random::<u64>() % (capped_ms + 1))
+        }
+      };
+
+      log::warn!(
+        "HubSpot APIエラーを検知（ステータス: {}、{}回目、{:?}後にリトライ）",
+        status,
+        attempt,
+        delay
+      );
+      tokio::time::sleep(delay).await;
+    }
+  }
+
+  /// HubSpotトークンを検証してアカウント情報を取得
+  pub async fn verify_token(&self) -> Result<AccountDetails> {
+    self.get_account_details().await
+  }
+
+  /// HubSpotアカウント詳細情報を取得
+  pub async fn get_account_details(&self) -> Result<AccountDetails> {
+    let url = "https://api.hubapi.com/account-info/v3/details";
+
+    let response = self
+      .send_with_retry(|| self.client.get(url).bearer_auth(&self.token).send())
+      .await?;
+
+    if !response.status().is_success() {
+      return Err(anyhow!("無効なトークンです: {}", response.status()));
+    }
+
+    let account_details: AccountDetails = response.json().await?;
+    Ok(account_details)
+  }
+
+  /// バッチでHubSpotレコードを検索
+  /// 複数のSalesforce IDを一度に検索して効率化。チャンクごとの検索は`search_concurrency`を
+  /// 上限に並行実行し(429対応は`send_with_retry`内の`rate_limiter`がプール全体で間引く)、
+  /// 大量チャンクでも逐次実行に比べて壁時計時間を短縮する
+  pub async fn batch_find_records(
+    &self,
+    object_type: &str,
+    property_name: &str,
+    property_values: &[String],
+  ) -> Result<HashMap<String, String>> {
+    let batch_size = 100; // HubSpot APIのINフィルタの制限に合わせる
+    let url = format!(
+      "https://api.hubapi.com/crm/v3/objects/{}/search",
+      object_type
+    );
+
+    let chunk_results: Vec<Result<HashMap<String, String>>> = stream::iter(
+      property_values.chunks(batch_size),
+    )
+    .map(|chunk| self.search_chunk(&url, property_name, chunk))
+    .buffer_unordered(self.search_concurrency)
+    .collect()
+    .await;
+
+    let mut found_records = HashMap::new();
+    for chunk_result in chunk_results {
+      found_records.extend(chunk_result?);
+    }
+
+    Ok(found_records)
+  }
+
+  /// 1チャンク分のSalesforce IDをHubSpotで検索し、`after`カーソルを辿り切って結果を集約する
+  async fn search_chunk(
+    &self,
+    url: &str,
+    property_name: &str,
+    chunk: &[String],
+  ) -> Result<HashMap<String, String>> {
+    let mut found_records = HashMap::new();
+    let mut after: Option<String> = None;
+
+    loop {
+      let mut search_request = serde_json::json!({
+          "filterGroups": [{
+              "filters": [{
+                  "propertyName": property_name,
+                  "operator": "IN",
+                  "values": chunk
+              }]
+          }],
+          "properties": ["hs_object_id", property_name],
+          "limit": 100
+      });
+      if let Some(after) = &after {
+        search_request["after"] = serde_json::Value::String(after.clone());
+      }
+
+      let response = self
+        .send_with_retry(|| {
+          self
+            .client
+            .post(url)
+            .bearer_auth(&self.token)
+            .json(&search_request)
+            .send()
+        })
+        .await?;
+
+      if !response.status().is_success() {
+        break;
+      }
+
+      let search_result: SearchResult = response.json().await?;
+
+      // 検索結果からSalesforce ID -> HubSpot IDのマッピングを作成
+      for record in search_result.results {
+        if let Some(sf_id) = record.properties.get(property_name) {
+          found_records.insert(sf_id.clone(), record.id);
+        }
+      }
+
+      after = search_result.paging.and_then(|p| p.next).map(|n| n.after);
+      if after.is_none() {
+        break;
+      }
+
+      // レート制限対応のための遅延
+      tokio::time::sleep(tokio::time::Duration::from_millis(self.rate_limit_delay)).await;
+    }
+
+    Ok(found_records)
+  }
+
+  /// ファイルパスからHubSpotファイル情報を取得
+  pub async fn get_file_by_path(&self, file_path: &str) -> Result<Option<FileInfo>> {
+    // URLエンコード
+    let encoded_path =
+      url::form_urlencoded::byte_serialize(file_path.as_bytes()).collect::<String>();
+
+    let url = format!(
+      "https://api.hubapi.com/files/v3/files/stat/{}",
+      encoded_path
+    );
+
+    let response = self
+      .send_with_retry(|| self.client.get(&url).bearer_auth(&self.token).send())
+      .await?;
+
+    if response.status().is_success() {
+      let data: serde_json::Value = response.json().await?;
+
+      if let Some(file_data) = data.get("file") {
+        let file_info = FileInfo {
+          id: file_data["id"].as_str().unwrap_or("").to_string(),
+          name: file_data["name"].as_str().unwrap_or("").to_string(),
+          path: file_data["path"].as_str().unwrap_or("").to_string(),
+          url: file_data["url"].as_str().map(|s| s.to_string()),
+        };
+        return Ok(Some(file_info));
+      }
+    }
+
+    Ok(None)
+  }
+
+  /// base64データからHubSpotにファイルをアップロード
+  pub async fn upload_file_from_base64(&self, base64_data: &str, filename: &str) -> Result<String> {
+    // base64デコード
+    let file_content = base64::engine::general_purpose::STANDARD
+      .decode(base64_data)
+      .map_err(|e| anyhow!("base64デコードエラー: {}", e))?;
+
+    let url = "https://api.hubapi.com/files/v3/files";
+
+    let response = self
+      .send_with_retry(|| {
+        // マルチパートは一度送信すると消費されるため、リトライのたびに作り直す
+        let form = reqwest::multipart::Form::new()
+          .text("options", r#"{"access": "PRIVATE"}"#)
+          .text("folderPath", "salesforce")
+          .part(
+            "file",
+            reqwest::multipart::Part::bytes(file_content.clone())
+              .file_name(filename.to_string())
+              .mime_str("application/octet-stream")
+              .expect("固定のMIMEタイプは常に有効"),
+          );
+
+        self.client.post(url).bearer_auth(&self.token).multipart(form).send()
+      })
+      .await?;
+
+    if response.status().is_success() {
+      let data: serde_json::Value = response.json().await?;
+      let file_id = data["id"].as_str().unwrap_or("");
+      Ok(file_id.to_string())
+    } else {
+      Err(anyhow!(
+        "ファイルアップロードに失敗しました: {}",
+        response.status()
+      ))
+    }
+  }
+
+  /// アップロード済みファイルのメタデータを再取得する（アップロード直後の整合性検証用）
+  pub async fn get_file_metadata(&self, file_id: &str) -> Result<Option<FileMetadata>> {
+    let url = format!("https://api.hubapi.com/files/v3/files/{}", file_id);
+
+    let response = self
+      .send_with_retry(|| self.client.get(&url).bearer_auth(&self.token).send())
+      .await?;
+
+    if response.status().is_success() {
+      let data: serde_json::Value = response.json().await?;
+      Ok(Some(FileMetadata {
+        id: data["id"].as_str().unwrap_or(file_id).to_string(),
+        size: data["size"].as_u64(),
+      }))
+    } else {
+      Ok(None)
+    }
+  }
+
+  /// レコードにノートを作成(ファイル添付付き)。成功時は作成されたノートのHubSpot IDを返す
+  /// `file_refs`はネイティブHubSpotファイル(`hs_attachment_ids`で関連付け)・外部ストレージに
+  /// 退避したファイル(本文末尾へのリンクとして埋め込み)のどちらも混在できる
+  pub async fn create_note_for_record(
+    &self,
+    hubspot_record_id: &str,
+    object_type: &str,
+    note_content: &str,
+    file_refs: &[crate::file_backend::FileRef],
+  ) -> Result<String> {
+    // オブジェクトタイプに応じた関連付けタイプIDを決定
+    let association_type_id = match object_type {
+      "contacts" => 202,
+      "companies" => 190,
+      "deals" => 214,
+      "tickets" => 226,
+      _ => 202, // デフォルトはcontacts
+    };
+
+    // 現在時刻をミリ秒で取得
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)?
+      .as_millis()
+      .to_string();
+
+    let mut native_file_ids = Vec::new();
+    let mut external_links = Vec::new();
+    for file_ref in file_refs {
+      match file_ref {
+        crate::file_backend::FileRef::HubSpotFile(file_id) => native_file_ids.push(file_id.clone()),
+        crate::file_backend::FileRef::External { url, filename } => external_links.push(format!(
+          "<a href=\"{}\">{}</a>",
+          escape_html(url),
+          escape_html(filename)
+        )),
+      }
+    }
+
+    let note_body = if external_links.is_empty() {
+      note_content.to_string()
+    } else {
+      format!("{}<br>添付ファイル: {}", note_content, external_links.join(", "))
+    };
+
+    // ノート作成リクエストを構築
+    let note_request = CreateNoteRequest {
+      properties: NoteProperties {
+        hs_note_body: note_body,
+        hs_timestamp: timestamp,
+        hs_attachment_ids: if native_file_ids.is_empty() {
+          None
+        } else {
+          Some(native_file_ids.join(";"))
+        },
+      },
+      associations: vec![Association {
+        to: AssociationTarget {
+          id: hubspot_record_id.to_string(),
+        },
+        types: vec![AssociationType {
+          association_category: "HUBSPOT_DEFINED".to_string(),
+          association_type_id,
+        }],
+      }],
+    };
+
+    let url = "https://api.hubapi.com/crm/v3/objects/notes";
+
+    let response = self
+      .send_with_retry(|| self.client.post(url).bearer_auth(&self.token).json(&note_request).send())
+      .await?;
+
+    if response.status().is_success() {
+      let data: serde_json::Value = response.json().await?;
+      let note_id = data["id"].as_str().unwrap_or("").to_string();
+      log::info!("ノート作成成功: {} (note_id={})", hubspot_record_id, note_id);
+      Ok(note_id)
+    } else {
+      let status = response.status();
+      let error_text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+      Err(anyhow!(
+        "ノート作成に失敗しました: {} - {}",
+        status,
+        error_text
+      ))
+    }
+  }
+
+  /// すべてのHubSpotオブジェクトを取得（標準 + カスタム）
+  pub async fn get_all_objects(&self) -> Result<Vec<crate::commands::HubSpotObject>> {
+    let mut objects = Vec::new();
+
+    // 標準オブジェクトを追加
+    objects.extend(vec![
+      crate::commands::HubSpotObject {
+        object_type_id: "contacts".to_string(),
+        name: "contacts".to_string(),
+        label: "コンタクト".to_string(),
+      },
+      crate::commands::HubSpotObject {
+        object_type_id: "companies".to_string(),
+        name: "companies".to_string(),
+        label: "会社".to_string(),
+      },
+      crate::commands::HubSpotObject {
+        object_type_id: "deals".to_string(),
+        name: "deals".to_string(),
+        label: "取引".to_string(),
+      },
+      crate::commands::HubSpotObject {
+        object_type_id: "tickets".to_string(),
+        name: "tickets".to_string(),
+        label: "チケット".to_string(),
+      },
+    ]);
+
+    // カスタムオブジェクトを取得
+    match self.get_custom_objects().await {
+      Ok(custom_objects) => {
+        let count = custom_objects.len();
+        objects.extend(custom_objects);
+        log::info!("カスタムオブジェクト: {}件", count);
+      }
+      Err(e) => {
+        log::warn!("カスタムオブジェクト取得エラー: {}", e);
+      }
+    }
+
+    Ok(objects)
+  }
+
+  /// カスタムオブジェクトを取得
+  async fn get_custom_objects(&self) -> Result<Vec<crate::commands::HubSpotObject>> {
+    let mut all_results = Vec::new();
+    let mut after: Option<String> = None;
+
+    loop {
+      let url = match &after {
+        Some(after) => format!("https://api.hubapi.com/crm/v3/schemas?after={}", after),
+        None => "https://api.hubapi.com/crm/v3/schemas".to_string(),
+      };
+
+      let response = self
+        .send_with_retry(|| self.client.get(&url).bearer_auth(&self.token).send())
+        .await?;
+
+      if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response
+          .text()
+          .await
+          .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(anyhow!(
+          "カスタムオブジェクト取得エラー: {} - {}",
+          status,
+          error_text
+        ));
+      }
+
+      let schema_response: SchemaResponse = response.json().await?;
+      after = schema_response.paging.and_then(|p| p.next).map(|n| n.after);
+      all_results.extend(schema_response.results);
+
+      if after.is_none() {
+        break;
+      }
+    }
+
+    let custom_objects: Vec<crate::commands::HubSpotObject> = all_results
+      .into_iter()
+      .filter(|obj| {
+        !matches!(
+          obj.id.as_str(),
+          "contacts" | "companies" | "deals" | "tickets"
+        )
+      })
+      .map(|obj| crate::commands::HubSpotObject {
+        object_type_id: obj.id.clone(),
+        name: obj.name,
+        label: obj.labels.plural.to_string(),
+      })
+      .collect();
+
+    Ok(custom_objects)
+  }
+}
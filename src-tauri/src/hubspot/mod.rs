@@ -1,5 +1,5 @@
 pub mod client;
 pub mod object_types;
 
-pub use client::HubSpotService;
+pub use client::{HubSpotService, MAX_FILE_SIZE_BYTES};
 pub use object_types::build_record_url;
@@ -0,0 +1,224 @@
+// 再開可能な処理実行を管理するジャーナルモジュール
+// レコードごとの処理結果を追記ログに書き込み、一定件数ごとにチェックポイントへ畳み込む
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// この件数ごとにログをチェックポイントへ畳み込み、ログファイルを切り詰める
+const CHECKPOINT_INTERVAL: usize = 64;
+
+/// 1レコード分の処理完了イベント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEvent {
+  pub salesforce_id: String,
+  pub hubspot_record_id: Option<String>,
+  pub uploaded_file_ids: Vec<String>,
+  pub note_created: bool,
+}
+
+/// チェックポイントスナップショット（完了済みレコードの集合）
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Checkpoint {
+  completed: HashMap<String, RunEvent>,
+}
+
+/// 実行中の処理に紐づくジャーナル（追記ログ + チェックポイント）
+pub struct RunJournal {
+  run_id: String,
+  log_path: PathBuf,
+  checkpoint_path: PathBuf,
+  pending_since_checkpoint: usize,
+  checkpoint: Checkpoint,
+}
+
+impl RunJournal {
+  /// ジャーナルファイルを格納するディレクトリ（なければ作成）
+  fn runs_dir(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let base = app
+      .path()
+      .app_data_dir()
+      .context("アプリデータディレクトリの取得に失敗しました")?;
+    let dir = base.join("runs");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+  }
+
+  /// 入力ファイルの組み合わせから決定的なrun_idを導出する
+  /// 同じ入力でコマンドを再実行した際に同じジャーナルへ自然に合流できるようにするため
+  pub fn derive_run_id(inputs: &[&str]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    for input in inputs {
+      hasher.update(input.as_bytes());
+      hasher.update(b"\0");
+    }
+    format!("{:x}", hasher.finalize())[..32].to_string()
+  }
+
+  /// run_idに対応するジャーナルを開く（既存のチェックポイントとログがあれば読み込んでリプレイする）
+  pub fn open(app: &tauri::AppHandle, run_id: &str) -> Result<Self> {
+    let dir = Self::runs_dir(app)?;
+    let log_path = dir.join(format!("{}.log.jsonl", run_id));
+    let checkpoint_path = dir.join(format!("{}.checkpoint.json", run_id));
+
+    let checkpoint = if checkpoint_path.exists() {
+      let data = fs::read_to_string(&checkpoint_path)?;
+      serde_json::from_str(&data).unwrap_or_default()
+    } else {
+      Checkpoint::default()
+    };
+
+    let mut journal = Self {
+      run_id: run_id.to_string(),
+      log_path,
+      checkpoint_path,
+      pending_since_checkpoint: 0,
+      checkpoint,
+    };
+
+    journal.replay_log()?;
+    log::info!(
+      "ジャーナルを開始: run_id={}, 完了済み={}件",
+      journal.run_id,
+      journal.checkpoint.completed.len()
+    );
+
+    Ok(journal)
+  }
+
+  /// 前回のチェックポイント以降に残っているログの末尾をチェックポイントへ反映する
+  fn replay_log(&mut self) -> Result<()> {
+    if !self.log_path.exists() {
+      return Ok(());
+    }
+
+    let file = File::open(&self.log_path)?;
+    let reader = BufReader::new(file);
+    let mut replayed = 0;
+
+    for line in reader.lines() {
+      let line = line?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      let event: RunEvent = serde_json::from_str(&line)?;
+      self
+        .checkpoint
+        .completed
+        .insert(event.salesforce_id.clone(), event);
+      replayed += 1;
+    }
+
+    if replayed > 0 {
+      log::info!("ジャーナルログをリプレイ: {}件", replayed);
+    }
+    self.pending_since_checkpoint = replayed;
+    Ok(())
+  }
+
+  /// このsalesforce_idが既に処理済みかどうか
+  pub fn is_complete(&self, salesforce_id: &str) -> bool {
+    self.checkpoint.completed.contains_key(salesforce_id)
+  }
+
+  /// レコード処理完了をログへ追記し、必要ならチェックポイントを行う
+  pub fn record_event(&mut self, event: RunEvent) -> Result<()> {
+    let mut file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.log_path)?;
+    writeln!(file, "{}", serde_json::to_string(&event)?)?;
+    file.flush()?;
+
+    self
+      .checkpoint
+      .completed
+      .insert(event.salesforce_id.clone(), event);
+    self.pending_since_checkpoint += 1;
+
+    if self.pending_since_checkpoint >= CHECKPOINT_INTERVAL {
+      self.checkpoint()?;
+    }
+
+    Ok(())
+  }
+
+  /// ログをチェックポイントスナップショットへ畳み込み、ログファイルを切り詰める
+  pub fn checkpoint(&mut self) -> Result<()> {
+    fs::write(&self.checkpoint_path, serde_json::to_string(&self.checkpoint)?)?;
+    fs::write(&self.log_path, "")?;
+    self.pending_since_checkpoint = 0;
+    log::info!(
+      "ジャーナルをチェックポイント化: run_id={}, 完了={}件",
+      self.run_id,
+      self.checkpoint.completed.len()
+    );
+    Ok(())
+  }
+
+  pub fn completed_count(&self) -> usize {
+    self.checkpoint.completed.len()
+  }
+
+  pub fn run_id(&self) -> &str {
+    &self.run_id
+  }
+
+  /// 実行完了後にジャーナルファイルを削除する
+  pub fn cleanup(&self) -> Result<()> {
+    let _ = fs::remove_file(&self.log_path);
+    let _ = fs::remove_file(&self.checkpoint_path);
+    Ok(())
+  }
+}
+
+/// 未完了実行の概要
+#[derive(Debug, Serialize)]
+pub struct IncompleteRun {
+  pub run_id: String,
+  pub completed_records: usize,
+  pub updated_at: String,
+}
+
+/// 未完了のジャーナル（チェックポイントファイルが残っているもの）をすべて列挙する
+pub fn list_incomplete_runs(app: &tauri::AppHandle) -> Result<Vec<IncompleteRun>> {
+  let dir = RunJournal::runs_dir(app)?;
+  let mut runs = Vec::new();
+
+  for entry in fs::read_dir(&dir)? {
+    let entry = entry?;
+    let path = entry.path();
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+      continue;
+    };
+    let Some(run_id) = file_name.strip_suffix(".checkpoint.json") else {
+      continue;
+    };
+
+    let data = fs::read_to_string(&path)?;
+    let checkpoint: Checkpoint = serde_json::from_str(&data).unwrap_or_default();
+    let updated_at = entry
+      .metadata()
+      .and_then(|m| m.modified())
+      .ok()
+      .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+      .map(|d| {
+        chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)
+          .unwrap_or_default()
+          .to_rfc3339()
+      })
+      .unwrap_or_default();
+
+    runs.push(IncompleteRun {
+      run_id: run_id.to_string(),
+      completed_records: checkpoint.completed.len(),
+      updated_at,
+    });
+  }
+
+  Ok(runs)
+}
@@ -0,0 +1,86 @@
+// レコードの処理結果をJUnit形式のXMLレポートとして書き出すモジュール
+// Salesforceオブジェクトのプレフィックスごとに<testsuite>を、処理した各レコードを<testcase>として出力し、
+// CIパイプラインがテスト結果と同じ形で移行結果を読み取り、未完了レコードが残っていればジョブを失敗させられるようにする
+use crate::commands::RecordResult;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// XMLの属性値として安全に埋め込めるようエスケープする
+fn escape_xml(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+/// キャンセルによりスキップされたレコードか
+fn is_skipped(record: &RecordResult) -> bool {
+  record.error.as_deref() == Some("cancelled")
+}
+
+/// "partial"/"error"に相当する失敗レコードか（キャンセルは除く）
+fn is_failure(record: &RecordResult) -> bool {
+  !is_skipped(record) && record.error.is_some()
+}
+
+/// レコード結果一覧からJUnit形式のXMLレポートを生成し、指定パスへ書き出す
+pub fn write_junit_report(path: &Path, records: &[RecordResult]) -> Result<()> {
+  let mut by_prefix: HashMap<&str, Vec<&RecordResult>> = HashMap::new();
+  for record in records {
+    let prefix = record.salesforce_id.get(..3).unwrap_or(&record.salesforce_id);
+    by_prefix.entry(prefix).or_default().push(record);
+  }
+
+  let mut prefixes: Vec<&str> = by_prefix.keys().copied().collect();
+  prefixes.sort_unstable();
+
+  let mut xml = String::new();
+  xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  xml.push_str("<testsuites>\n");
+
+  for prefix in prefixes {
+    let suite_records = &by_prefix[prefix];
+    let failures = suite_records.iter().filter(|r| is_failure(r)).count();
+    let skipped = suite_records.iter().filter(|r| is_skipped(r)).count();
+
+    xml.push_str(&format!(
+      "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">\n",
+      escape_xml(prefix),
+      suite_records.len(),
+      failures,
+      skipped
+    ));
+
+    for record in suite_records.iter() {
+      xml.push_str(&format!(
+        "    <testcase name=\"{}\" classname=\"{}\">\n",
+        escape_xml(&record.salesforce_id),
+        escape_xml(&record.hubspot_object)
+      ));
+      xml.push_str(&format!(
+        "      <properties>\n        <property name=\"record_url\" value=\"{}\"/>\n        <property name=\"note_created\" value=\"{}\"/>\n      </properties>\n",
+        escape_xml(&record.record_url),
+        record.note_created
+      ));
+
+      if is_skipped(record) {
+        xml.push_str("      <skipped/>\n");
+      } else if is_failure(record) {
+        let message = record.error.as_deref().unwrap_or("unknown error");
+        xml.push_str(&format!("      <failure message=\"{}\"/>\n", escape_xml(message)));
+      }
+
+      xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n");
+  }
+
+  xml.push_str("</testsuites>\n");
+
+  std::fs::write(path, xml)?;
+  Ok(())
+}
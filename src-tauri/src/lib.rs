@@ -1,8 +1,17 @@
 // モジュール宣言
+mod archive_bundle;
 mod auth;
+mod chatter_attachments;
+mod chatter_checkpoint;
 mod commands;
 mod csv;
+mod file_backend;
 mod hubspot;
+mod journal;
+mod junit_report;
+mod mqtt_sink;
+mod note_ledger;
+mod upload_index;
 
 use tauri::{Emitter, Manager};
 use tauri_plugin_deep_link::DeepLinkExt;
@@ -41,18 +50,31 @@ pub fn run() {
       commands::logout,
       commands::start_oauth_flow,
       commands::save_oauth_tokens,
+      commands::list_profiles,
+      commands::switch_profile,
+      commands::remove_profile,
+      commands::ensure_valid_token,
+      commands::needs_reconsent,
       // ビジネスロジック
       commands::get_hubspot_objects,
       commands::analyze_csv_files,
       commands::analyze_chatter_files,
       commands::process_file_mapping,
+      commands::resume_file_mapping,
+      commands::list_incomplete_runs,
+      commands::benchmark_upload,
+      commands::export_last_report,
+      commands::resolve_chatter_attachments,
       commands::process_chatter_migration,
       commands::save_result_csv,
-      commands::cleanup_temp_csv
+      commands::cleanup_temp_csv,
+      commands::cancel_migration
     ])
     .manage(auth::OAuthState {
-      pending_auth: std::sync::Mutex::new(None),
+      pending_verifier: std::sync::Mutex::new(None),
     })
+    .manage(commands::ReportState::default())
+    .manage(commands::CancellationState::default())
     .setup(|app| {
       // デバッグビルド時のみログプラグインを有効化
       if cfg!(debug_assertions) {
@@ -63,6 +85,27 @@ pub fn run() {
         )?;
       }
 
+      // OAuthコールバックをリモートworker経由ではなくローカルで完結させるためのループバックサーバー
+      let loopback_server = auth::loopback::start(app.handle().clone())
+        .map_err(|e| format!("ループバックサーバーの起動に失敗しました: {}", e))?;
+      app.manage(loopback_server);
+
+      // アクセストークンの期限切れ前にバックグラウンドで定期的にリフレッシュするタスク
+      let refresh_app_handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        loop {
+          tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+          match auth::SecureStorage::maybe_refresh().await {
+            Ok(true) => {
+              log::info!("アクセストークンをバックグラウンドでリフレッシュしました");
+              let _ = refresh_app_handle.emit("token://refreshed", ());
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("バックグラウンドトークンリフレッシュに失敗しました: {}", e),
+          }
+        }
+      });
+
       // Deep Link処理をRust側で実装
       // アプリ起動時のDeep Linkを処理
       if let Ok(Some(urls)) = app.deep_link().get_current() {
@@ -73,6 +116,7 @@ pub fn run() {
             if let Some(window) = app.get_webview_window("main") {
               let _ = window.emit("deep-link-urls", &urls);
             }
+            auth::loopback::handle_deep_link_url(app.handle(), url.as_str());
           }
         }
       } else {
@@ -90,6 +134,7 @@ pub fn run() {
             if let Some(window) = handle.get_webview_window("main") {
               let _ = window.emit("deep-link-urls", &urls);
             }
+            auth::loopback::handle_deep_link_url(&handle, url.as_str());
           }
         }
       });
@@ -0,0 +1,172 @@
+// 処理済みChatterレコードをMQTTブローカーへパブリッシュするシンクモジュール
+// イベント駆動の外部連携向けに、Vecとして返す代わりに（あるいはそれに加えて）
+// プッシュ型のストリームとしてレコードを配信できるようにする
+use crate::csv::processor::ProcessableChatterRecord;
+use anyhow::{anyhow, Context, Result};
+use rumqttc::{AsyncClient, LastWill, MqttOptions, QoS};
+use std::time::Duration;
+
+/// トピック階層のルートプレフィックス
+const TOPIC_PREFIX: &str = "connect/v1";
+
+/// MQTT接続の生存状態を通知するステータストピック
+const STATUS_TOPIC: &str = "connect/v1/status";
+
+/// `connect/v1/{object_type}/{salesforce_id}/chatter`形式のトピックを構築・解析する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatterTopic {
+  pub object_type: String,
+  pub salesforce_id: String,
+}
+
+impl ChatterTopic {
+  /// トピック文字列を構築する
+  pub fn build(&self) -> String {
+    format!(
+      "{}/{}/{}/chatter",
+      TOPIC_PREFIX, self.object_type, self.salesforce_id
+    )
+  }
+
+  /// トピック文字列から`ChatterTopic`を復元する（ワイルドカード購読からの親ID逆引き用）
+  pub fn parse(topic: &str) -> Option<Self> {
+    let suffix = topic.strip_prefix(&format!("{}/", TOPIC_PREFIX))?;
+    let mut parts = suffix.splitn(3, '/');
+    let object_type = parts.next()?.to_string();
+    let salesforce_id = parts.next()?.to_string();
+    if parts.next()? != "chatter" {
+      return None;
+    }
+    Some(Self {
+      object_type,
+      salesforce_id,
+    })
+  }
+}
+
+/// MQTTパブリッシュ時のQoS設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishQos {
+  AtMostOnce,
+  AtLeastOnce,
+  ExactlyOnce,
+}
+
+impl From<PublishQos> for QoS {
+  fn from(qos: PublishQos) -> Self {
+    match qos {
+      PublishQos::AtMostOnce => QoS::AtMostOnce,
+      PublishQos::AtLeastOnce => QoS::AtLeastOnce,
+      PublishQos::ExactlyOnce => QoS::ExactlyOnce,
+    }
+  }
+}
+
+/// MQTTシンクの接続設定
+#[derive(Debug, Clone)]
+pub struct MqttSinkConfig {
+  /// ブローカーのホスト名
+  pub host: String,
+  /// ブローカーのポート
+  pub port: u16,
+  /// クライアントID
+  pub client_id: String,
+  /// パブリッシュ時のQoS
+  pub qos: PublishQos,
+}
+
+/// 処理済みChatterレコードをMQTTへパブリッシュするシンク
+pub struct MqttSink {
+  client: AsyncClient,
+  qos: PublishQos,
+}
+
+impl MqttSink {
+  /// ブローカーへ接続し、Last Will（死亡通知）を設定した上でBirthメッセージを送信する
+  pub async fn connect(config: MqttSinkConfig) -> Result<Self> {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    options.set_last_will(LastWill::new(
+      STATUS_TOPIC,
+      "offline",
+      QoS::AtLeastOnce,
+      true,
+    ));
+
+    let (client, mut event_loop) = AsyncClient::new(options, 10);
+
+    // イベントループを駆動するバックグラウンドタスク
+    // （rumqttcはポーリング駆動のため、誰かが継続的にpollし続ける必要がある）
+    tokio::spawn(async move {
+      loop {
+        if event_loop.poll().await.is_err() {
+          break;
+        }
+      }
+    });
+
+    client
+      .publish(STATUS_TOPIC, QoS::AtLeastOnce, true, "online")
+      .await
+      .context("Birthメッセージのパブリッシュに失敗しました")?;
+
+    Ok(Self {
+      client,
+      qos: config.qos,
+    })
+  }
+
+  /// 1件のProcessableChatterRecordをJSONとしてパブリッシュする
+  pub async fn publish_record(
+    &self,
+    object_type: &str,
+    record: &ProcessableChatterRecord,
+  ) -> Result<()> {
+    let topic = ChatterTopic {
+      object_type: object_type.to_string(),
+      salesforce_id: record.salesforce_id.clone(),
+    }
+    .build();
+
+    let payload = serde_json::to_vec(record).context("Chatterレコードのシリアライズに失敗しました")?;
+
+    self
+      .client
+      .publish(topic, self.qos.into(), false, payload)
+      .await
+      .map_err(|e| anyhow!("Chatterレコードのパブリッシュに失敗しました: {}", e))
+  }
+
+  /// 複数のProcessableChatterRecordを順にパブリッシュする
+  /// 各レコードは親（salesforce_id）単位で1メッセージにまとめられているため、
+  /// 1件ずつのパブリッシュであっても親のフィード全体が単一メッセージとして不可分に届く
+  pub async fn publish_batch(
+    &self,
+    object_type: &str,
+    records: &[ProcessableChatterRecord],
+  ) -> Result<()> {
+    for record in records {
+      self.publish_record(object_type, record).await?;
+    }
+    log::info!(
+      "MQTTパブリッシュ完了: {}件のChatterレコード（object_type={}）",
+      records.len(),
+      object_type
+    );
+    Ok(())
+  }
+
+  /// Deathメッセージを送信して切断する
+  pub async fn disconnect(&self) -> Result<()> {
+    self
+      .client
+      .publish(STATUS_TOPIC, QoS::AtLeastOnce, true, "offline")
+      .await
+      .context("Deathメッセージのパブリッシュに失敗しました")?;
+    self
+      .client
+      .disconnect()
+      .await
+      .context("MQTT切断に失敗しました")
+  }
+}
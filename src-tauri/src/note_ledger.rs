@@ -0,0 +1,99 @@
+// Chatter移行のノート/添付ファイルを冪等に再実行するためのレジャーを管理するモジュール
+// (salesforce_id, FeedItem.id)ごとに作成済みノートのHubSpot IDとノート内容のSHA-256ハッシュを記録し、
+// 再実行時に内容が変わっていなければ同じノートを重複作成しない。添付ファイルは
+// content_document_id + version_idの組をキーとして再アップロードを避ける
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+const LEDGER_FILE_NAME: &str = "chatter_note_ledger.json";
+
+/// 作成済みノート1件分のレジャーエントリ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteLedgerEntry {
+  pub note_id: String,
+  pub content_hash: String,
+}
+
+/// レジャー本体。アプリデータディレクトリにJSONとして永続化する
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NoteLedgerData {
+  /// "salesforce_id:feed_item_id" -> 作成済みノートのエントリ
+  notes: HashMap<String, NoteLedgerEntry>,
+  /// "content_document_id:version_id" -> アップロード済みHubSpotファイルID
+  files: HashMap<String, String>,
+}
+
+/// Chatter移行の冪等性を担保するノート/ファイルレジャー
+pub struct NoteLedger {
+  path: PathBuf,
+  data: NoteLedgerData,
+}
+
+impl NoteLedger {
+  fn ledger_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let base = app
+      .path()
+      .app_data_dir()
+      .context("アプリデータディレクトリの取得に失敗しました")?;
+    fs::create_dir_all(&base)?;
+    Ok(base.join(LEDGER_FILE_NAME))
+  }
+
+  /// ノートキー "salesforce_id:feed_item_id" を組み立てる
+  pub fn note_key(salesforce_id: &str, feed_item_id: &str) -> String {
+    format!("{}:{}", salesforce_id, feed_item_id)
+  }
+
+  /// ファイルキー "content_document_id:version_id" を組み立てる
+  pub fn file_key(content_document_id: &str, version_id: &str) -> String {
+    format!("{}:{}", content_document_id, version_id)
+  }
+
+  /// 永続化されたレジャーを読み込む（無ければ空のレジャーから開始）
+  pub fn load(app: &tauri::AppHandle) -> Result<Self> {
+    let path = Self::ledger_path(app)?;
+    let data = fs::read_to_string(&path)
+      .ok()
+      .and_then(|raw| serde_json::from_str(&raw).ok())
+      .unwrap_or_default();
+
+    log::info!(
+      "Chatterノートレジャー読み込み: ノート{}件、ファイル{}件",
+      data.notes.len(),
+      data.files.len()
+    );
+
+    Ok(Self { path, data })
+  }
+
+  /// 指定キーの作成済みノートを探す
+  pub fn lookup_note(&self, key: &str) -> Option<&NoteLedgerEntry> {
+    self.data.notes.get(key)
+  }
+
+  /// ノート作成結果を記録し、即座にディスクへ書き込む
+  pub fn record_note(&mut self, key: String, note_id: String, content_hash: String) -> Result<()> {
+    self.data.notes.insert(key, NoteLedgerEntry { note_id, content_hash });
+    self.persist()
+  }
+
+  /// 指定キーに対応するアップロード済みHubSpotファイルIDを探す
+  pub fn lookup_file(&self, key: &str) -> Option<String> {
+    self.data.files.get(key).cloned()
+  }
+
+  /// アップロード済みファイルIDを記録し、即座にディスクへ書き込む
+  pub fn record_file(&mut self, key: String, hubspot_file_id: String) -> Result<()> {
+    self.data.files.insert(key, hubspot_file_id);
+    self.persist()
+  }
+
+  fn persist(&self) -> Result<()> {
+    fs::write(&self.path, serde_json::to_string(&self.data)?)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,62 @@
+// アップロード済みファイルのコンテンツハッシュ → HubSpotファイルIDの対応を管理するモジュール
+// 同一バイト列のファイルが異なるContentVersionとして複数回アップロードされることを防ぐ
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// インデックスファイルの名前
+const INDEX_FILE_NAME: &str = "upload_index.json";
+
+/// コンテンツのSHA-256ハッシュを16進文字列で計算する
+pub fn compute_content_hash(bytes: &[u8]) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(bytes);
+  format!("{:x}", hasher.finalize())
+}
+
+/// ハッシュ → HubSpotファイルIDの対応表
+/// アプリデータディレクトリにJSONとして永続化し、アプリ再起動後も重複排除が効くようにする
+pub struct UploadIndex {
+  path: PathBuf,
+  entries: HashMap<String, String>,
+}
+
+impl UploadIndex {
+  fn index_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let base = app
+      .path()
+      .app_data_dir()
+      .context("アプリデータディレクトリの取得に失敗しました")?;
+    fs::create_dir_all(&base)?;
+    Ok(base.join(INDEX_FILE_NAME))
+  }
+
+  /// 永続化されたインデックスを読み込む（無ければ空のインデックスから開始）
+  pub fn open(app: &tauri::AppHandle) -> Result<Self> {
+    let path = Self::index_path(app)?;
+    let entries = if path.exists() {
+      let data = fs::read_to_string(&path)?;
+      serde_json::from_str(&data).unwrap_or_default()
+    } else {
+      HashMap::new()
+    };
+    log::info!("アップロードインデックスを読み込み: {}件", entries.len());
+    Ok(Self { path, entries })
+  }
+
+  /// コンテンツハッシュに対応するHubSpotファイルIDを探す
+  /// 未登録の場合は呼び出し側で従来のパスベースの存在確認にフォールバックする
+  pub fn lookup(&self, content_hash: &str) -> Option<String> {
+    self.entries.get(content_hash).cloned()
+  }
+
+  /// コンテンツハッシュとHubSpotファイルIDの対応を記録し、即座にディスクへ書き込む
+  pub fn record(&mut self, content_hash: String, hubspot_file_id: String) -> Result<()> {
+    self.entries.insert(content_hash, hubspot_file_id);
+    fs::write(&self.path, serde_json::to_string(&self.entries)?)?;
+    Ok(())
+  }
+}